@@ -11,22 +11,21 @@ fn rfc3339_humantime_seconds(b: &mut test::Bencher) {
     let time = UNIX_EPOCH + Duration::new(1_483_228_799, 0);
     let mut buf = Vec::with_capacity(100);
     b.iter(|| {
-        buf.truncate(0);
+        buf.clear();
         write!(&mut buf, "{}", format_rfc3339(time)).unwrap()
     });
 }
 
 #[bench]
 fn rfc3339_chrono(b: &mut test::Bencher) {
-    use chrono::{DateTime, NaiveDateTime, Utc};
+    use chrono::{DateTime, Utc};
     use chrono::format::Item;
     use chrono::format::Item::*;
     use chrono::format::Numeric::*;
     use chrono::format::Fixed::*;
     use chrono::format::Pad::*;
 
-    let time = DateTime::<Utc>::from_utc(
-        NaiveDateTime::from_timestamp(1_483_228_799, 0), Utc);
+    let time = DateTime::<Utc>::from_timestamp(1_483_228_799, 0).unwrap();
     let mut buf = Vec::with_capacity(100);
 
     // formatting code from env_logger
@@ -49,7 +48,7 @@ fn rfc3339_chrono(b: &mut test::Bencher) {
 
 
     b.iter(|| {
-        buf.truncate(0);
+        buf.clear();
         write!(&mut buf, "{}", time.format_with_items(ITEMS.iter().cloned()))
             .unwrap()
     });
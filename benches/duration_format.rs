@@ -0,0 +1,29 @@
+#![feature(test)]
+extern crate test;
+
+use std::io::Write;
+use std::time::Duration;
+
+use humantime::format_duration;
+
+#[bench]
+fn humantime_whole_seconds(b: &mut test::Bencher) {
+    let duration = Duration::new(93_784, 0);
+    let mut buf = Vec::with_capacity(100);
+    b.iter(|| {
+        buf.clear();
+        write!(&mut buf, "{}", format_duration(test::black_box(duration)))
+            .unwrap()
+    });
+}
+
+#[bench]
+fn humantime_with_nanos(b: &mut test::Bencher) {
+    let duration = Duration::new(93_784, 5_006_007);
+    let mut buf = Vec::with_capacity(100);
+    b.iter(|| {
+        buf.clear();
+        write!(&mut buf, "{}", format_duration(test::black_box(duration)))
+            .unwrap()
+    });
+}
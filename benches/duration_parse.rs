@@ -0,0 +1,25 @@
+#![feature(test)]
+extern crate test;
+
+use humantime::parse_duration;
+
+#[bench]
+fn humantime_seconds(b: &mut test::Bencher) {
+    b.iter(|| {
+        parse_duration(test::black_box("30s")).unwrap()
+    });
+}
+
+#[bench]
+fn humantime_long_unit(b: &mut test::Bencher) {
+    b.iter(|| {
+        parse_duration(test::black_box("30seconds")).unwrap()
+    });
+}
+
+#[bench]
+fn humantime_combo(b: &mut test::Bencher) {
+    b.iter(|| {
+        parse_duration(test::black_box("1year 2months 3days 4h 5min 6s 7ms")).unwrap()
+    });
+}
@@ -0,0 +1,80 @@
+/// Kind of value detected by [`classify`](classify)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// Looks like a duration `30s`, parse with `parse_duration`
+    Duration,
+    /// Looks like a timestamp `2018-02-14T00:28:07Z`, parse with
+    /// `parse_rfc3339_weak`
+    Timestamp,
+    /// Neither of the above
+    Unknown,
+}
+
+/// Guesses whether the string is a duration or a timestamp
+///
+/// This is a cheap structural check, the value isn't parsed, so it may
+/// still be invalid. The rules are:
+///
+/// 1. If it starts with four digits, dash, two digits and a dash (like
+///    `2018-02-`), it's a timestamp.
+/// 2. If it starts with a digit and consists only of ASCII letters,
+///    digits, dots and whitespace, it's a duration. This includes `0`.
+/// 3. Otherwise it's unknown.
+///
+/// Surrounding whitespace is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{classify, Kind};
+///
+/// assert_eq!(classify("30s"), Kind::Duration);
+/// assert_eq!(classify("2018-02-14T00:28:07Z"), Kind::Timestamp);
+/// assert_eq!(classify("garbage"), Kind::Unknown);
+/// ```
+pub fn classify(s: &str) -> Kind {
+    let b = s.trim().as_bytes();
+    if b.len() >= 8 && b[..4].iter().all(u8::is_ascii_digit) &&
+        b[4] == b'-' && b[5].is_ascii_digit() && b[6].is_ascii_digit() &&
+        b[7] == b'-'
+    {
+        return Kind::Timestamp;
+    }
+    let duration_chars = b.iter().all(|&c| {
+        c.is_ascii_alphanumeric() || c == b'.' || c.is_ascii_whitespace()
+    });
+    if matches!(b.first(), Some(c) if c.is_ascii_digit()) && duration_chars {
+        return Kind::Duration;
+    }
+    Kind::Unknown
+}
+
+#[cfg(test)]
+mod test {
+    use super::{classify, Kind};
+
+    #[test]
+    fn durations() {
+        assert_eq!(classify("30s"), Kind::Duration);
+        assert_eq!(classify(" 1h 30min "), Kind::Duration);
+        assert_eq!(classify("1.5h"), Kind::Duration);
+        assert_eq!(classify("0"), Kind::Duration);
+        assert_eq!(classify("2018"), Kind::Duration);
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(classify("2018-02-14T00:00:00Z"), Kind::Timestamp);
+        assert_eq!(classify("2018-02-14 00:00:00"), Kind::Timestamp);
+        assert_eq!(classify("2018-02-14T00:00:00+03:00"), Kind::Timestamp);
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(classify("garbage"), Kind::Unknown);
+        assert_eq!(classify(""), Kind::Unknown);
+        assert_eq!(classify("-5s"), Kind::Unknown);
+        assert_eq!(classify("5s!"), Kind::Unknown);
+        assert_eq!(classify("18-02-14"), Kind::Unknown);
+    }
+}
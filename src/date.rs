@@ -1,11 +1,15 @@
+use std::borrow::Cow;
 use std::error::Error as StdError;
 use std::fmt;
 use std::str;
 use std::time::{SystemTime, Duration, UNIX_EPOCH};
 
+use crate::duration::{format_duration, truncate_units, humanize, Style};
+
 #[cfg(target_os="cloudabi")]
 mod max {
     pub const SECONDS: u64 = ::std::u64::MAX / 1_000_000_000;
+    pub const YEAR: u64 = 2554;
     #[allow(unused)]
     pub const TIMESTAMP: &'static str = "2554-07-21T23:34:33Z";
 }
@@ -17,6 +21,7 @@ mod max {
 ))]
 mod max {
     pub const SECONDS: u64 = ::std::i32::MAX as u64;
+    pub const YEAR: u64 = 2038;
     #[allow(unused)]
     pub const TIMESTAMP: &'static str = "2038-01-19T03:14:07Z";
 }
@@ -28,10 +33,41 @@ mod max {
 ))]
 mod max {
     pub const SECONDS: u64 = 253_402_300_800-1;  // last second of year 9999
+    pub const YEAR: u64 = 9999;
     #[allow(unused)]
     pub const TIMESTAMP: &str = "9999-12-31T23:59:59Z";
 }
 
+/// The smallest year that timestamp parsers accept
+pub const MIN_YEAR: u64 = 1970;
+
+/// The largest year that timestamp parsers accept
+///
+/// This is `9999` on most platforms, but it's lower where `SystemTime`
+/// can't represent such a value (e.g. `2038` on 32-bit unix). Note: on such
+/// platforms only part of the year is accepted, see
+/// [`MAX_TIMESTAMP_SECS`](MAX_TIMESTAMP_SECS).
+pub const MAX_YEAR: u64 = max::YEAR;
+
+/// The largest timestamp (in seconds since the epoch) that parsers accept
+///
+/// On most platforms this is the last second of year 9999.
+pub const MAX_TIMESTAMP_SECS: u64 = max::SECONDS;
+
+/// The longest timestamp produced by formatters
+///
+/// This is nanosecond precision with a UTC offset,
+/// `2018-02-14T00:28:07.123456789+00:00`. Useful for sizing buffers.
+pub const RFC3339_MAX_LEN: usize = "2018-02-14T00:28:07.123456789+00:00".len();
+
+/// Returns the length of the shortest timestamp `parse_rfc3339` accepts
+///
+/// This is a timestamp without fractional part in UTC,
+/// `2018-02-14T00:28:07Z`.
+pub const fn rfc3339_min_len() -> usize {
+    "2018-02-14T00:28:07Z".len()
+}
+
 /// Error parsing datetime (timestamp)
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Error {
@@ -41,6 +77,8 @@ pub enum Error {
     InvalidDigit,
     /// Other formatting errors
     InvalidFormat,
+    /// Start of the interval is later than its end
+    InvalidRange,
 }
 
 impl StdError for Error {}
@@ -51,22 +89,37 @@ impl fmt::Display for Error {
             Error::OutOfRange => write!(f, "numeric component is out of range"),
             Error::InvalidDigit => write!(f, "bad character where digit is expected"),
             Error::InvalidFormat => write!(f, "timestamp format is invalid"),
+            Error::InvalidRange => {
+                write!(f, "invalid interval, start is later than end")
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Precision {
+/// Number of fractional digits in a formatted timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// No fraction for whole seconds, nine digits otherwise
     Smart,
+    /// Seconds only, fraction is truncated
     Seconds,
+    /// Three digits
     Millis,
+    /// Six digits
     Micros,
+    /// Nine digits
     Nanos,
 }
 
 /// A wrapper type that allows you to Display a SystemTime
+///
+/// Formatting doesn't allocate: the value is built in a stack buffer and
+/// written to the formatter at once.
+///
+/// The third field is an offset east of UTC in seconds, `None` means UTC
+/// written as `Z`. The fourth one is a date and time separator.
 #[derive(Debug, Clone)]
-pub struct Rfc3339Timestamp(SystemTime, Precision);
+pub struct Rfc3339Timestamp(SystemTime, Precision, Option<i32>, u8);
 
 #[inline]
 /// Converts two digits given in ASCII to its proper decimal representation.
@@ -81,21 +134,113 @@ fn two_digits(b1: u8, b2: u8) -> Result<u64, Error> {
     two_digits_inner(b1 as char, b2 as char).ok_or(Error::InvalidDigit)
 }
 
+fn digit(b: u8) -> Result<u64, Error> {
+    match b {
+        b'0'..=b'9' => Ok((b - b'0') as u64),
+        _ => Err(Error::InvalidDigit),
+    }
+}
+
 /// Parse RFC3339 timestamp `2018-02-14T00:28:07Z`
 ///
-/// Supported feature: any precision of fractional
-/// digits `2018-02-14T00:28:07.133Z`.
+/// Supported features:
 ///
-/// Unsupported feature: localized timestamps. Only UTC is supported.
+/// 1. Any precision of fractional digits `2018-02-14T00:28:07.133Z`.
+/// 2. Timezone offset instead of `Z`: `2018-02-14T03:28:07+03:00`. The
+///    offset is applied, i.e. returned value is always in UTC. Both
+///    `+00:00` and `-00:00` are the same as `Z`.
+/// 3. Lowercase `t` and `z` as allowed by RFC 3339, and space instead of
+///    `T` as produced by some databases: `2018-02-14 00:28:07Z`. The
+///    timezone is still required.
 pub fn parse_rfc3339(s: &str) -> Result<SystemTime, Error> {
-    if s.len() < "2018-02-14T00:28:07Z".len() {
+    parse_rfc3339_since_epoch(s).map(|d| UNIX_EPOCH + d)
+}
+
+/// Parse RFC3339 timestamp into duration since the epoch
+///
+/// Same as [`parse_rfc3339`](parse_rfc3339) followed by
+/// `duration_since(UNIX_EPOCH)`, but without the round trip through
+/// `SystemTime`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_rfc3339_since_epoch;
+///
+/// assert_eq!(parse_rfc3339_since_epoch("2018-02-14T00:28:07.5Z"),
+///            Ok(Duration::new(1_518_568_087, 500_000_000)));
+/// ```
+pub fn parse_rfc3339_since_epoch(s: &str) -> Result<Duration, Error> {
+    if s.len() < rfc3339_min_len() {
         return Err(Error::InvalidFormat);
     }
     let b = s.as_bytes();
-    if b[10] != b'T' || b.last() != Some(&b'Z') {
+    if !matches!(b[10], b'T' | b't' | b' ') {
         return Err(Error::InvalidFormat);
     }
-    parse_rfc3339_weak(s)
+    if !matches!(b.last(), Some(b'Z') | Some(b'z')) {
+        let tz = b.len().saturating_sub("+00:00".len());
+        if tz < 19 || (b[tz] != b'+' && b[tz] != b'-') || b[tz+3] != b':' {
+            return Err(Error::InvalidFormat);
+        }
+    }
+    parse_weak_since_epoch(&uppercase_designators(s))
+}
+
+/// Replaces lowercase `t` separator and `z` timezone with uppercase ones
+fn uppercase_designators(s: &str) -> Cow<'_, str> {
+    let b = s.as_bytes();
+    let lower_t = b.get(10) == Some(&b't');
+    let lower_z = b.len() > 19 && b.last() == Some(&b'z');
+    if !lower_t && !lower_z {
+        return Cow::Borrowed(s);
+    }
+    let mut buf = s.to_string();
+    if lower_t {
+        buf.replace_range(10..11, "T");
+    }
+    if lower_z {
+        buf.pop();
+        buf.push('Z');
+    }
+    Cow::Owned(buf)
+}
+
+/// Parse RFC3339 timestamp with two-digit year `18-02-14T00:28:07Z`
+///
+/// Years up to and including `pivot` are in 2000s, others in 1900s. So
+/// with pivot `50`, `18` is `2018` and `85` is `1985`. The rest of the
+/// timestamp is parsed as in [`parse_rfc3339`](parse_rfc3339). Years
+/// before 1970 are out of range as usual.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{parse_rfc3339, parse_rfc3339_2digit_year};
+///
+/// assert_eq!(parse_rfc3339_2digit_year("18-02-14T00:28:07Z", 50),
+///            parse_rfc3339("2018-02-14T00:28:07Z"));
+/// ```
+pub fn parse_rfc3339_2digit_year(s: &str, pivot: u64)
+    -> Result<SystemTime, Error>
+{
+    let b = s.as_bytes();
+    if b.len() < 3 || b[2] != b'-' {
+        return Err(Error::InvalidFormat);
+    }
+    let year = two_digits(b[0], b[1])?;
+    let century = if year <= pivot { "20" } else { "19" };
+    parse_rfc3339(&format!("{}{}", century, s))
+}
+
+/// Parse RFC3339 timestamp surrounded by whitespace `2018-02-14T00:28:07Z\n`
+///
+/// Leading and trailing ASCII whitespace is ignored, which is convenient
+/// for values read from files or log lines. Otherwise it's the same as
+/// [`parse_rfc3339`](parse_rfc3339).
+pub fn parse_rfc3339_trim(s: &str) -> Result<SystemTime, Error> {
+    parse_rfc3339(s.trim_matches(|c: char| c.is_ascii_whitespace()))
 }
 
 /// Parse RFC3339-like timestamp `2018-02-14 00:28:07`
@@ -104,14 +249,44 @@ pub fn parse_rfc3339(s: &str) -> Result<SystemTime, Error> {
 ///
 /// 1. Any precision of fractional digits `2018-02-14 00:28:07.133`.
 /// 2. Supports timestamp with or without either of `T` or `Z`
-/// 3. Anything valid for [`parse_rfc3339`](parse_rfc3339) is valid for this function
+/// 3. Timezone offset in `+03:00`, `+0300` or `+03` form
+/// 4. Anything valid for [`parse_rfc3339`](parse_rfc3339) is valid for this function
 ///
-/// Unsupported feature: localized timestamps. Only UTC is supported, even if
-/// `Z` is not specified.
+/// Timestamp without `Z` or an offset is treated as UTC.
 ///
 /// This function is intended to use for parsing human input. Whereas
 /// `parse_rfc3339` is for strings generated programmatically.
 pub fn parse_rfc3339_weak(s: &str) -> Result<SystemTime, Error> {
+    parse_weak_since_epoch(s).map(|d| UNIX_EPOCH + d)
+}
+
+/// Parse anything reasonably looking like RFC3339 `2018-02-14t00:28:07z`
+///
+/// This is the most permissive timestamp parser in the crate. It accepts
+/// everything [`parse_rfc3339_weak`](parse_rfc3339_weak) does and
+/// additionally:
+///
+/// 1. Lowercase `t` as a date and time separator.
+/// 2. Lowercase `z` as a timezone.
+/// 3. Leading and trailing ASCII whitespace.
+///
+/// Timestamp without `Z` or an offset is treated as UTC. Use
+/// [`parse_rfc3339`](parse_rfc3339) for strings generated programmatically.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{parse_rfc3339, parse_rfc3339_loose};
+///
+/// assert_eq!(parse_rfc3339_loose(" 2018-02-14t00:28:07z\n"),
+///            parse_rfc3339("2018-02-14T00:28:07Z"));
+/// ```
+pub fn parse_rfc3339_loose(s: &str) -> Result<SystemTime, Error> {
+    let s = s.trim_matches(|c: char| c.is_ascii_whitespace());
+    parse_rfc3339_weak(&uppercase_designators(s))
+}
+
+fn parse_weak_since_epoch(s: &str) -> Result<Duration, Error> {
     if s.len() < "2018-02-14T00:28:07".len() {
         return Err(Error::InvalidFormat);
     }
@@ -128,7 +303,7 @@ pub fn parse_rfc3339_weak(s: &str) -> Result<SystemTime, Error> {
     let minute = two_digits(b[14], b[15])?;
     let mut second = two_digits(b[17], b[18])?;
 
-    if year < 1970 || hour > 23 || minute > 59 || second > 60 {
+    if year < MIN_YEAR || hour > 23 || minute > 59 || second > 60 {
         return Err(Error::OutOfRange);
     }
     // TODO(tailhook) should we check that leaps second is only on midnight ?
@@ -136,8 +311,468 @@ pub fn parse_rfc3339_weak(s: &str) -> Result<SystemTime, Error> {
         second = 59;
     }
 
+    let days = days_from_civil(year, month, day)?;
+
+    let time = second + minute * 60 + hour * 3600;
+
+    let mut nanos = 0;
+    let mut idx = 19;
+    if b.get(idx) == Some(&b'.') {
+        let mut mult = 100_000_000;
+        idx += 1;
+        while idx < b.len() {
+            match b[idx] {
+                b'0'..=b'9' => {
+                    nanos += mult * (b[idx] - b'0') as u32;
+                    mult /= 10;
+                }
+                // zone is validated below, only one of `Z` and offset
+                b'Z' | b'+' | b'-' => break,
+                _ => return Err(Error::InvalidDigit),
+            }
+            idx += 1;
+        }
+    }
+    let offset = match b.get(idx) {
+        None => 0,
+        Some(&b'Z') if idx == b.len()-1 => 0,
+        Some(&b'+') | Some(&b'-') => parse_offset(&b[idx..])?,
+        Some(_) => return Err(Error::InvalidFormat),
+    };
+
+    let total_seconds = (time + days * 86400) as i64 - offset;
+    if total_seconds < 0 || total_seconds as u64 > max::SECONDS {
+        return Err(Error::OutOfRange);
+    }
+    let total_seconds = total_seconds as u64;
+
+    Ok(Duration::new(total_seconds, nanos))
+}
+
+/// Parses timezone offset `+03:00`, `+0300` or `+03` into seconds east of UTC
+fn parse_offset(b: &[u8]) -> Result<i64, Error> {
+    let (hours, minutes) = match b.len() {
+        6 if b[3] == b':' => (two_digits(b[1], b[2])?, two_digits(b[4], b[5])?),
+        5 => (two_digits(b[1], b[2])?, two_digits(b[3], b[4])?),
+        3 => (two_digits(b[1], b[2])?, 0),
+        _ => return Err(Error::InvalidFormat),
+    };
+    if hours > 23 || minutes > 59 {
+        return Err(Error::OutOfRange);
+    }
+    let seconds = (hours * 3600 + minutes * 60) as i64;
+    Ok(if b[0] == b'-' { -seconds } else { seconds })
+}
+
+/// Parse time of day `14:30` into duration since midnight
+///
+/// Supported formats are `HH:MM`, `HH:MM:SS` and `HH:MM:SS.fraction`
+/// (with any number of fractional digits). Keywords `noon` and `midnight`
+/// (in any case) are `12:00:00` and `00:00:00` respectively.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_time_of_day;
+///
+/// assert_eq!(parse_time_of_day("14:30"), Ok(Duration::new(52200, 0)));
+/// assert_eq!(parse_time_of_day("noon"), Ok(Duration::new(43200, 0)));
+/// ```
+pub fn parse_time_of_day(s: &str) -> Result<Duration, Error> {
+    if s.eq_ignore_ascii_case("noon") {
+        return Ok(Duration::new(12 * 3600, 0));
+    }
+    if s.eq_ignore_ascii_case("midnight") {
+        return Ok(Duration::new(0, 0));
+    }
+    let b = s.as_bytes();
+    if b.len() < "00:00".len() || b[2] != b':' {
+        return Err(Error::InvalidFormat);
+    }
+    let hour = two_digits(b[0], b[1])?;
+    let minute = two_digits(b[3], b[4])?;
+    let mut second = 0;
+    let mut nanos = 0;
+    if b.len() > 5 {
+        if b.len() < "00:00:00".len() || b[5] != b':' {
+            return Err(Error::InvalidFormat);
+        }
+        second = two_digits(b[6], b[7])?;
+        if b.len() > 8 {
+            if b[8] != b'.' {
+                return Err(Error::InvalidFormat);
+            }
+            let mut mult = 100_000_000;
+            for &c in &b[9..] {
+                match c {
+                    b'0'..=b'9' => {
+                        nanos += mult * (c - b'0') as u32;
+                        mult /= 10;
+                    }
+                    _ => return Err(Error::InvalidDigit),
+                }
+            }
+        }
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(Error::OutOfRange);
+    }
+    Ok(Duration::new(hour * 3600 + minute * 60 + second, nanos))
+}
+
+/// Parse SRT subtitle timecode `00:01:30,500` into duration
+///
+/// The format is fixed: `HH:MM:SS,mmm` where `mmm` is milliseconds. Hours
+/// aren't limited to a day, so up to `99:59:59,999` is accepted.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_srt_timecode;
+///
+/// assert_eq!(parse_srt_timecode("00:01:30,500"),
+///            Ok(Duration::new(90, 500_000_000)));
+/// ```
+pub fn parse_srt_timecode(s: &str) -> Result<Duration, Error> {
+    let b = s.as_bytes();
+    if b.len() != "00:00:00,000".len() ||
+        b[2] != b':' || b[5] != b':' || b[8] != b','
+    {
+        return Err(Error::InvalidFormat);
+    }
+    let hour = two_digits(b[0], b[1])?;
+    let minute = two_digits(b[3], b[4])?;
+    let second = two_digits(b[6], b[7])?;
+    let millis = two_digits(b[9], b[10])? * 10 + digit(b[11])?;
+    if minute > 59 || second > 59 {
+        return Err(Error::OutOfRange);
+    }
+    Ok(Duration::new(hour * 3600 + minute * 60 + second,
+                     millis as u32 * 1_000_000))
+}
+
+/// Parse WebVTT timestamp `00:01:30.500` or `01:30.500` into duration
+///
+/// The format is `HH:MM:SS.mmm` or `MM:SS.mmm` where `mmm` is
+/// milliseconds. Hours may have more than two digits.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_vtt_timecode;
+///
+/// assert_eq!(parse_vtt_timecode("00:01:30.500"),
+///            Ok(Duration::new(90, 500_000_000)));
+/// assert_eq!(parse_vtt_timecode("01:30.500"),
+///            Ok(Duration::new(90, 500_000_000)));
+/// ```
+pub fn parse_vtt_timecode(s: &str) -> Result<Duration, Error> {
+    let b = s.as_bytes();
+    if b.len() < "00:00.000".len() {
+        return Err(Error::InvalidFormat);
+    }
+    let tail = &b[b.len() - "00:00.000".len()..];
+    if tail[2] != b':' || tail[5] != b'.' {
+        return Err(Error::InvalidFormat);
+    }
+    let minute = two_digits(tail[0], tail[1])?;
+    let second = two_digits(tail[3], tail[4])?;
+    let millis = two_digits(tail[6], tail[7])? * 10 + digit(tail[8])?;
+    let hour = match &b[..b.len() - tail.len()] {
+        [] => 0,
+        [hours @ .., b':'] if hours.len() >= 2 && !hours.contains(&b':') => {
+            let mut hour = 0u64;
+            for &c in hours {
+                if !c.is_ascii_digit() {
+                    return Err(Error::InvalidDigit);
+                }
+                hour = hour.checked_mul(10)
+                    .and_then(|h| h.checked_add((c - b'0') as u64))
+                    .ok_or(Error::OutOfRange)?;
+            }
+            hour
+        }
+        _ => return Err(Error::InvalidFormat),
+    };
+    if minute > 59 || second > 59 {
+        return Err(Error::OutOfRange);
+    }
+    let secs = hour.checked_mul(3600)
+        .and_then(|s| s.checked_add(minute * 60 + second))
+        .ok_or(Error::OutOfRange)?;
+    Ok(Duration::new(secs, millis as u32 * 1_000_000))
+}
+
+/// Checks whether two timestamps denote the same instant
+///
+/// Both values are parsed with [`parse_rfc3339_weak`](parse_rfc3339_weak),
+/// so `Z`, `+00:00` and `+0000` are all equivalent ways to write UTC, and
+/// other offsets are compared after converting to UTC.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::rfc3339_equivalent;
+///
+/// assert!(rfc3339_equivalent(
+///     "2018-02-14T00:28:07Z", "2018-02-14T00:28:07+00:00").unwrap());
+/// assert!(rfc3339_equivalent(
+///     "2018-02-14T00:28:07Z", "2018-02-14T03:28:07+03:00").unwrap());
+/// ```
+pub fn rfc3339_equivalent(a: &str, b: &str) -> Result<bool, Error> {
+    Ok(parse_rfc3339_weak(a)? == parse_rfc3339_weak(b)?)
+}
+
+/// Checks whether two timestamps are within `tolerance` from each other
+///
+/// This is useful to compare timestamps that went through formatters of
+/// different precision, which truncate the fractional part.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{approx_eq, parse_rfc3339};
+///
+/// let a = parse_rfc3339("2018-02-14T00:28:07.123456789Z").unwrap();
+/// let b = parse_rfc3339("2018-02-14T00:28:07.123Z").unwrap();
+/// assert!(approx_eq(a, b, Duration::from_millis(1)));
+/// ```
+pub fn approx_eq(a: SystemTime, b: SystemTime, tolerance: Duration) -> bool {
+    let diff = match a.duration_since(b) {
+        Ok(diff) => diff,
+        Err(e) => e.duration(),
+    };
+    diff <= tolerance
+}
+
+/// Rounds the timestamp down to a multiple of granularity since the epoch
+///
+/// Useful for bucketing events, e.g. by minute. Timestamps before the
+/// epoch are rounded towards the past too. Zero granularity returns the
+/// timestamp unchanged. See [`round_to_nearest`](round_to_nearest) for
+/// rounding to the closest multiple.
+///
+/// If the rounded value can't be represented by `SystemTime` (only
+/// possible near its platform-specific limits), the timestamp is returned
+/// unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{round_to, parse_rfc3339};
+///
+/// let t = parse_rfc3339("2018-02-14T00:28:07Z").unwrap();
+/// assert_eq!(round_to(t, Duration::from_secs(60)),
+///            parse_rfc3339("2018-02-14T00:28:00Z").unwrap());
+/// ```
+pub fn round_to(t: SystemTime, granularity: Duration) -> SystemTime {
+    round(t, granularity, false)
+}
+
+/// Rounds the timestamp to the nearest multiple of granularity
+///
+/// Same as [`round_to`](round_to), but exact halves are rounded up. If
+/// rounding up overflows `SystemTime`, the timestamp is rounded down
+/// instead.
+pub fn round_to_nearest(t: SystemTime, granularity: Duration) -> SystemTime {
+    round(t, granularity, true)
+}
+
+fn round(t: SystemTime, granularity: Duration, nearest: bool) -> SystemTime {
+    let step = granularity.as_nanos() as i128;
+    if step == 0 {
+        return t;
+    }
+    let nanos = match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_nanos() as i128,
+        Err(e) => -(e.duration().as_nanos() as i128),
+    };
+    let shifted = if nearest { nanos + step / 2 } else { nanos };
+    let delta = shifted - shifted.rem_euclid(step) - nanos;
+    let abs = Duration::new(
+        (delta.unsigned_abs() / 1_000_000_000) as u64,
+        (delta.unsigned_abs() % 1_000_000_000) as u32);
+    let rounded = if delta < 0 {
+        t.checked_sub(abs)
+    } else {
+        t.checked_add(abs)
+    };
+    match rounded {
+        Some(rounded) => rounded,
+        None if nearest => round(t, granularity, false),
+        None => t,
+    }
+}
+
+/// Formats the time between two RFC3339 timestamps `1h 2m`
+///
+/// Both values are parsed with [`parse_rfc3339`](parse_rfc3339). The order
+/// of arguments doesn't matter, as the absolute difference is used. Only
+/// two largest units are shown (as in `format_duration` output), the rest
+/// is truncated.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::time_between;
+///
+/// assert_eq!(time_between("2018-02-14T00:28:07Z", "2018-02-14T01:30:15Z")
+///            .unwrap(), "1h 2m");
+/// ```
+pub fn time_between(a: &str, b: &str) -> Result<String, Error> {
+    let a = parse_rfc3339(a)?;
+    let b = parse_rfc3339(b)?;
+    let diff = match a.duration_since(b) {
+        Ok(diff) => diff,
+        Err(e) => e.duration(),
+    };
+    Ok(format_duration(truncate_units(diff, 2)).to_string())
+}
+
+/// Returns age in completed years for the birth date `1990-05-17`
+///
+/// The birth date is either an RFC 3339 full date or a timestamp accepted by
+/// [`parse_rfc3339`](parse_rfc3339). Both dates are taken in UTC and time of
+/// day is ignored, so the age changes at midnight UTC on the birthday. Those
+/// born on February 29 get one year older on March 1 in non-leap years.
+///
+/// Returns [`OutOfRange`](Error::OutOfRange) if `now` is before the birth
+/// date.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{age_years, parse_rfc3339};
+///
+/// let now = parse_rfc3339("2018-02-14T00:28:07Z").unwrap();
+/// assert_eq!(age_years("1990-05-17", now), Ok(27));
+/// assert_eq!(age_years("1990-02-14", now), Ok(28));
+/// ```
+pub fn age_years(birth: &str, now: SystemTime) -> Result<u64, Error> {
+    let (by, bm, bd) = civil_from_days(parse_day_number(birth)?);
+    let (ny, nm, nd) = civil_from_days(day_number(now)?);
+    if (ny, nm, nd) < (by, bm, bd) {
+        return Err(Error::OutOfRange);
+    }
+    let age = ny - by;
+    if (nm, nd) < (bm, bd) {
+        Ok(age as u64 - 1)
+    } else {
+        Ok(age as u64)
+    }
+}
+
+/// Returns the signed number of days from date `a` to date `b`
+///
+/// Dates are either RFC 3339 full dates `2018-02-14` or timestamps
+/// accepted by [`parse_rfc3339`](parse_rfc3339). Calendar days are counted
+/// in UTC and time of day is ignored, so `2018-02-14T23:59:59Z` to
+/// `2018-02-15T00:00:00Z` is one day. The result is negative if `b` is
+/// before `a`.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::days_between;
+///
+/// assert_eq!(days_between("2020-02-28", "2020-03-01"), Ok(2));
+/// assert_eq!(days_between("2019-03-01", "2019-02-28"), Ok(-1));
+/// ```
+pub fn days_between(a: &str, b: &str) -> Result<i64, Error> {
+    Ok(parse_day_number(b)? - parse_day_number(a)?)
+}
+
+/// Parses a full date or a timestamp into the number of days since epoch
+fn parse_day_number(s: &str) -> Result<i64, Error> {
+    let t = if s.len() == "2018-02-14".len() {
+        parse_rfc3339(&format!("{}T00:00:00Z", s))?
+    } else {
+        parse_rfc3339(s)?
+    };
+    day_number(t)
+}
+
+fn day_number(t: SystemTime) -> Result<i64, Error> {
+    let secs = t.duration_since(UNIX_EPOCH)
+        .map_err(|_| Error::OutOfRange)?.as_secs();
+    Ok((secs / 86400) as i64)
+}
+
+/// Formats time relative to now `3 minutes ago`, or as date `2018-02-14`
+///
+/// If `t` is within `threshold` from `now` (inclusive), the result is the
+/// difference in the largest unit with full name, truncated, followed by
+/// `ago` (or preceded by `in` for times in the future). Otherwise the
+/// result is the UTC date of `t` as in RFC 3339.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{format_relative_or_absolute, parse_rfc3339};
+///
+/// let now = parse_rfc3339("2018-02-14T00:28:07Z").unwrap();
+/// let day = Duration::from_secs(86400);
+/// let sent = now - Duration::from_secs(200);
+/// assert_eq!(format_relative_or_absolute(sent, now, day), "3 minutes ago");
+/// let sent = now - day * 2;
+/// assert_eq!(format_relative_or_absolute(sent, now, day), "2018-02-12");
+/// ```
+pub fn format_relative_or_absolute(t: SystemTime, now: SystemTime,
+    threshold: Duration)
+    -> String
+{
+    let (diff, future) = match now.duration_since(t) {
+        Ok(diff) => (diff, false),
+        Err(e) => (e.duration(), true),
+    };
+    if diff > threshold {
+        return format_date(t);
+    }
+    let value = humanize(truncate_units(diff, 1), Style::Long);
+    if future {
+        format!("in {}", value)
+    } else {
+        format!("{} ago", value)
+    }
+}
+
+/// Clamps timestamp into the range supported by this crate
+///
+/// Times before the epoch become `1970-01-01T00:00:00Z`, and times after
+/// [`MAX_TIMESTAMP_SECS`](MAX_TIMESTAMP_SECS) (the end of year 9999 on most
+/// platforms) become the last nanosecond of that second. The result can
+/// always be formatted by `format_rfc3339*` functions.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use humantime::clamp_to_range;
+///
+/// let before = UNIX_EPOCH - Duration::new(1, 0);
+/// assert_eq!(clamp_to_range(before), UNIX_EPOCH);
+/// ```
+pub fn clamp_to_range(system_time: SystemTime) -> SystemTime {
+    let max = UNIX_EPOCH + Duration::new(MAX_TIMESTAMP_SECS, 999_999_999);
+    if system_time < UNIX_EPOCH {
+        UNIX_EPOCH
+    } else if system_time > max {
+        max
+    } else {
+        system_time
+    }
+}
+
+/// Returns days before the month (in a non-leap year) and days in the month
+fn month_days(year: u64, month: u64) -> Result<(u64, u64), Error> {
     let leap = is_leap_year(year);
-    let (mut ydays, mdays) = match month {
+    Ok(match month {
         1 => (0, 31),
         2 if leap => (31, 29),
         2 => (31, 28),
@@ -152,51 +787,131 @@ pub fn parse_rfc3339_weak(s: &str) -> Result<SystemTime, Error> {
         11 => (304, 30),
         12 => (334, 31),
         _ => return Err(Error::OutOfRange),
-    };
+    })
+}
+
+/// Converts a date (since 1970) into the number of days since the epoch
+fn days_from_civil(year: u64, month: u64, day: u64) -> Result<u64, Error> {
+    let (mut ydays, mdays) = month_days(year, month)?;
     if day > mdays || day == 0 {
         return Err(Error::OutOfRange);
     }
     ydays += day - 1;
-    if leap && month > 2 {
+    if is_leap_year(year) && month > 2 {
         ydays += 1;
     }
 
     let leap_years = ((year - 1) - 1968) / 4 - ((year - 1) - 1900) / 100 + ((year - 1) - 1600) / 400;
-    let days = (year - 1970) * 365 + leap_years + ydays;
+    Ok((year - 1970) * 365 + leap_years + ydays)
+}
 
-    let time = second + minute * 60 + hour * 3600;
+/// Converts number of days since the epoch into `(year, month, day)`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    /* 2000-03-01 (mod 400 year, immediately after feb29 */
+    const LEAPOCH: i64 = 11017;
+    const DAYS_PER_400Y: i64 = 365*400 + 97;
+    const DAYS_PER_100Y: i64 = 365*100 + 24;
+    const DAYS_PER_4Y: i64 = 365*4 + 1;
 
-    let mut nanos = 0;
-    let mut mult = 100_000_000;
-    if b.get(19) == Some(&b'.') {
-        for idx in 20..b.len() {
-            if b[idx] == b'Z' {
-                if idx == b.len()-1 {
-                    break;
-                }
+    let days = days - LEAPOCH;
 
-                return Err(Error::InvalidDigit);
-            }
+    let mut qc_cycles = days / DAYS_PER_400Y;
+    let mut remdays = days % DAYS_PER_400Y;
+
+    if remdays < 0 {
+        remdays += DAYS_PER_400Y;
+        qc_cycles -= 1;
+    }
+
+    let mut c_cycles = remdays / DAYS_PER_100Y;
+    if c_cycles == 4 { c_cycles -= 1; }
+    remdays -= c_cycles * DAYS_PER_100Y;
 
-            nanos += mult * (b[idx] as char).to_digit(10).ok_or(Error::InvalidDigit)?;
-            mult /= 10;
+    let mut q_cycles = remdays / DAYS_PER_4Y;
+    if q_cycles == 25 { q_cycles -= 1; }
+    remdays -= q_cycles * DAYS_PER_4Y;
+
+    let mut remyears = remdays / 365;
+    if remyears == 4 { remyears -= 1; }
+    remdays -= remyears * 365;
+
+    let mut year = 2000 +
+        remyears + 4*q_cycles + 100*c_cycles + 400*qc_cycles;
+
+    let months = [31,30,31,30,31,31,30,31,30,31,31,29];
+    let mut mon = 0;
+    for mon_len in months.iter() {
+        mon += 1;
+        if remdays < *mon_len {
+            break;
         }
-    } else if b.len() != 19 && (b.len() > 20 || b[19] != b'Z') {
-        return Err(Error::InvalidFormat);
+        remdays -= *mon_len;
     }
+    let mday = remdays+1;
+    let mon = if mon + 2 > 12 {
+        year += 1;
+        mon - 10
+    } else {
+        mon + 2
+    };
+    (year, mon, mday)
+}
 
-    let total_seconds = time + days * 86400;
-    if total_seconds > max::SECONDS {
+/// Moves timestamp by a number of calendar months, keeping time of day
+///
+/// If the day doesn't exist in the target month, the last day of the month
+/// is used, e.g. Jan 31 plus one month is Feb 28 (or 29).
+pub(crate) fn add_months(system_time: SystemTime, months: i64)
+    -> Result<SystemTime, Error>
+{
+    let dur = system_time.duration_since(UNIX_EPOCH)
+        .map_err(|_| Error::OutOfRange)?;
+    let secs_of_day = dur.as_secs() % 86400;
+    let (year, mon, mday) = civil_from_days((dur.as_secs() / 86400) as i64);
+    let total = (year * 12 + mon - 1).checked_add(months)
+        .ok_or(Error::OutOfRange)?;
+    let year = total.div_euclid(12);
+    let mon = (total.rem_euclid(12) + 1) as u64;
+    if year < MIN_YEAR as i64 || year > MAX_YEAR as i64 {
         return Err(Error::OutOfRange);
     }
-
-    Ok(UNIX_EPOCH + Duration::new(total_seconds, nanos))
+    let year = year as u64;
+    let (_, mdays) = month_days(year, mon)?;
+    let days = days_from_civil(year, mon, (mday as u64).min(mdays))?;
+    let secs = days * 86400 + secs_of_day;
+    if secs > max::SECONDS {
+        return Err(Error::OutOfRange);
+    }
+    Ok(UNIX_EPOCH + Duration::new(secs, dur.subsec_nanos()))
 }
 
+#[allow(clippy::manual_is_multiple_of)]
 fn is_leap_year(y: u64) -> bool {
     y % 4 == 0 && (y % 100 != 0 || y % 400 == 0)
 }
 
+/// Format a timestamp with space separator `2018-02-14 00:28:07Z`
+///
+/// This is easier to read in logs than the `T` separator and is still
+/// accepted by [`parse_rfc3339`](parse_rfc3339). The value is always
+/// UTC.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use humantime::{format_rfc3339_space, Precision};
+///
+/// let t = UNIX_EPOCH + Duration::new(1_518_568_087, 123_000_000);
+/// assert_eq!(format_rfc3339_space(t, Precision::Millis).to_string(),
+///            "2018-02-14 00:28:07.123Z");
+/// ```
+pub fn format_rfc3339_space(system_time: SystemTime, precision: Precision)
+    -> Rfc3339Timestamp
+{
+    Rfc3339Timestamp(system_time, precision, None, b' ')
+}
+
 /// Format an RFC3339 timestamp `2018-02-14T00:28:07Z`
 ///
 /// This function formats timestamp with smart precision: i.e. if it has no
@@ -205,7 +920,67 @@ fn is_leap_year(y: u64) -> bool {
 ///
 /// The value is always UTC and ignores system timezone.
 pub fn format_rfc3339(system_time: SystemTime) -> Rfc3339Timestamp {
-    Rfc3339Timestamp(system_time, Precision::Smart)
+    Rfc3339Timestamp(system_time, Precision::Smart, None, b'T')
+}
+
+/// Format the date of a timestamp `2018-02-14`
+///
+/// This is the date part of [`format_rfc3339`](format_rfc3339) output,
+/// so the value is always UTC. Useful for naming daily log files.
+///
+/// # Panics
+///
+/// Panics if the time is before the epoch, as other formatting functions
+/// do.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use humantime::format_date;
+///
+/// let t = UNIX_EPOCH + Duration::new(1_518_568_087, 0);
+/// assert_eq!(format_date(t), "2018-02-14");
+/// ```
+pub fn format_date(system_time: SystemTime) -> String {
+    let dur = system_time.duration_since(UNIX_EPOCH)
+        .expect("all times should be after the epoch");
+    let (year, mon, mday) = civil_from_days((dur.as_secs() / 86400) as i64);
+    format!("{:04}-{:02}-{:02}", year, mon, mday)
+}
+
+/// Returns abbreviated day of the week of a timestamp `Wed`
+///
+/// One of `Mon`, `Tue`, `Wed`, `Thu`, `Fri`, `Sat` and `Sun`. The day is
+/// taken in UTC.
+///
+/// # Panics
+///
+/// Panics if the time is before the epoch, as other formatting functions
+/// do.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use humantime::format_weekday;
+///
+/// let t = UNIX_EPOCH + Duration::new(1_518_568_087, 0);
+/// assert_eq!(format_weekday(t), "Wed");
+/// ```
+pub fn format_weekday(system_time: SystemTime) -> &'static str {
+    // the epoch, 1970-01-01, is Thursday
+    const NAMES: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    let dur = system_time.duration_since(UNIX_EPOCH)
+        .expect("all times should be after the epoch");
+    NAMES[(dur.as_secs() / 86400 % 7) as usize]
+}
+
+/// Format an RFC3339 timestamp into a string `2018-02-14T00:28:07Z`
+///
+/// This is a shortcut for `format_rfc3339(system_time).to_string()`.
+pub fn timestamp_string(system_time: SystemTime) -> String {
+    format_rfc3339(system_time).to_string()
 }
 
 /// Format an RFC3339 timestamp `2018-02-14T00:28:07Z`
@@ -214,7 +989,7 @@ pub fn format_rfc3339(system_time: SystemTime) -> Rfc3339Timestamp {
 ///
 /// The value is always UTC and ignores system timezone.
 pub fn format_rfc3339_seconds(system_time: SystemTime) -> Rfc3339Timestamp {
-    Rfc3339Timestamp(system_time, Precision::Seconds)
+    Rfc3339Timestamp(system_time, Precision::Seconds, None, b'T')
 }
 
 /// Format an RFC3339 timestamp `2018-02-14T00:28:07.000Z`
@@ -223,7 +998,7 @@ pub fn format_rfc3339_seconds(system_time: SystemTime) -> Rfc3339Timestamp {
 ///
 /// The value is always UTC and ignores system timezone.
 pub fn format_rfc3339_millis(system_time: SystemTime) -> Rfc3339Timestamp {
-    Rfc3339Timestamp(system_time, Precision::Millis)
+    Rfc3339Timestamp(system_time, Precision::Millis, None, b'T')
 }
 
 /// Format an RFC3339 timestamp `2018-02-14T00:28:07.000000Z`
@@ -232,7 +1007,7 @@ pub fn format_rfc3339_millis(system_time: SystemTime) -> Rfc3339Timestamp {
 ///
 /// The value is always UTC and ignores system timezone.
 pub fn format_rfc3339_micros(system_time: SystemTime) -> Rfc3339Timestamp {
-    Rfc3339Timestamp(system_time, Precision::Micros)
+    Rfc3339Timestamp(system_time, Precision::Micros, None, b'T')
 }
 
 /// Format an RFC3339 timestamp `2018-02-14T00:28:07.000000000Z`
@@ -241,7 +1016,33 @@ pub fn format_rfc3339_micros(system_time: SystemTime) -> Rfc3339Timestamp {
 ///
 /// The value is always UTC and ignores system timezone.
 pub fn format_rfc3339_nanos(system_time: SystemTime) -> Rfc3339Timestamp {
-    Rfc3339Timestamp(system_time, Precision::Nanos)
+    Rfc3339Timestamp(system_time, Precision::Nanos, None, b'T')
+}
+
+/// Format an RFC3339 timestamp in local timezone `2018-02-14T03:28:07+03:00`
+///
+/// Precision is the same as in [`format_rfc3339`](format_rfc3339). The
+/// offset is read from the system timezone settings for the instant being
+/// formatted. Offsets that aren't whole minutes (local mean time in old
+/// timezone data) are rounded to the nearest minute. If local time would
+/// be before 1970, the timestamp is formatted in UTC with `Z` suffix.
+///
+/// Only available with `localtime` feature. Note: all other functions of
+/// this crate use UTC, this one is meant for operator-facing output only.
+#[cfg(feature="localtime")]
+pub fn format_rfc3339_local(system_time: SystemTime) -> Rfc3339Timestamp {
+    use chrono::{Local, Offset, TimeZone};
+
+    let secs = match system_time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => {
+            let d = e.duration();
+            -(d.as_secs() as i64) - (d.subsec_nanos() > 0) as i64
+        }
+    };
+    let offset = Local.timestamp_opt(secs, 0).single()
+        .map(|t| t.offset().fix().local_minus_utc());
+    Rfc3339Timestamp(system_time, Precision::Smart, offset, b'T')
 }
 
 impl Rfc3339Timestamp {
@@ -257,65 +1058,35 @@ impl fmt::Display for Rfc3339Timestamp {
 
         let dur = self.0.duration_since(UNIX_EPOCH)
             .expect("all times should be after the epoch");
-        let secs_since_epoch = dur.as_secs();
+        // offset can only be printed in whole minutes, so historical
+        // offsets like -04:56:02 are rounded to keep the instant intact
+        let tz = self.2.map(|tz| (tz + 30).div_euclid(60) * 60);
+        let (secs_since_epoch, tz) = match tz {
+            Some(tz) if tz < 0 => {
+                match dur.as_secs().checked_sub(tz.unsigned_abs() as u64) {
+                    Some(secs) => (secs, Some(tz)),
+                    // local time is before the epoch, fall back to UTC
+                    None => (dur.as_secs(), None),
+                }
+            }
+            Some(tz) => (dur.as_secs() + tz as u64, Some(tz)),
+            None => (dur.as_secs(), None),
+        };
         let nanos = dur.subsec_nanos();
 
         if secs_since_epoch >= 253_402_300_800 { // year 9999
             return Err(fmt::Error);
         }
 
-        /* 2000-03-01 (mod 400 year, immediately after feb29 */
-        const LEAPOCH: i64 = 11017;
-        const DAYS_PER_400Y: i64 = 365*400 + 97;
-        const DAYS_PER_100Y: i64 = 365*100 + 24;
-        const DAYS_PER_4Y: i64 = 365*4 + 1;
-
-        let days = (secs_since_epoch / 86400) as i64 - LEAPOCH;
         let secs_of_day = secs_since_epoch % 86400;
+        let (year, mon, mday) =
+            civil_from_days((secs_since_epoch / 86400) as i64);
 
-        let mut qc_cycles = days / DAYS_PER_400Y;
-        let mut remdays = days % DAYS_PER_400Y;
-
-        if remdays < 0 {
-            remdays += DAYS_PER_400Y;
-            qc_cycles -= 1;
-        }
-
-        let mut c_cycles = remdays / DAYS_PER_100Y;
-        if c_cycles == 4 { c_cycles -= 1; }
-        remdays -= c_cycles * DAYS_PER_100Y;
+        const BUF_INIT: [u8; RFC3339_MAX_LEN] =
+            *b"0000-00-00T00:00:00.000000000+00:00";
 
-        let mut q_cycles = remdays / DAYS_PER_4Y;
-        if q_cycles == 25 { q_cycles -= 1; }
-        remdays -= q_cycles * DAYS_PER_4Y;
-
-        let mut remyears = remdays / 365;
-        if remyears == 4 { remyears -= 1; }
-        remdays -= remyears * 365;
-
-        let mut year = 2000 +
-            remyears + 4*q_cycles + 100*c_cycles + 400*qc_cycles;
-
-        let months = [31,30,31,30,31,31,30,31,30,31,31,29];
-        let mut mon = 0;
-        for mon_len in months.iter() {
-            mon += 1;
-            if remdays < *mon_len {
-                break;
-            }
-            remdays -= *mon_len;
-        }
-        let mday = remdays+1;
-        let mon = if mon + 2 > 12 {
-            year += 1;
-            mon - 10
-        } else {
-            mon + 2
-        };
-
-        const BUF_INIT: [u8; 30] = *b"0000-00-00T00:00:00.000000000Z";
-
-        let mut buf: [u8; 30] = BUF_INIT;
+        let mut buf: [u8; RFC3339_MAX_LEN] = BUF_INIT;
+        buf[10] = self.3;
         buf[0] = b'0' + (year / 1000) as u8;
         buf[1] = b'0' + (year / 100 % 10) as u8;
         buf[2] = b'0' + (year / 10 % 10) as u8;
@@ -332,13 +1103,11 @@ impl fmt::Display for Rfc3339Timestamp {
         buf[18] = b'0' + (secs_of_day % 10) as u8;
 
         let offset = if self.1 == Seconds || nanos == 0 && self.1 == Smart {
-            buf[19] = b'Z';
             19
         } else if self.1 == Millis {
             buf[20] = b'0' + (nanos / 100_000_000) as u8;
             buf[21] = b'0' + (nanos / 10_000_000 % 10) as u8;
             buf[22] = b'0' + (nanos / 1_000_000 % 10) as u8;
-            buf[23] = b'Z';
             23
         } else if self.1 == Micros {
             buf[20] = b'0' + (nanos / 100_000_000) as u8;
@@ -347,7 +1116,6 @@ impl fmt::Display for Rfc3339Timestamp {
             buf[23] = b'0' + (nanos / 100_000 % 10) as u8;
             buf[24] = b'0' + (nanos / 10_000 % 10) as u8;
             buf[25] = b'0' + (nanos / 1_000 % 10) as u8;
-            buf[26] = b'Z';
             26
         } else {
             buf[20] = b'0' + (nanos / 100_000_000) as u8;
@@ -359,17 +1127,34 @@ impl fmt::Display for Rfc3339Timestamp {
             buf[26] = b'0' + (nanos / 100 % 10) as u8;
             buf[27] = b'0' + (nanos / 10 % 10) as u8;
             buf[28] = b'0' + (nanos % 10) as u8;
-            // 29th is 'Z'
             29
         };
 
+        let end = match tz {
+            None => {
+                buf[offset] = b'Z';
+                offset
+            }
+            Some(tz) => {
+                let tz_min = tz.unsigned_abs() / 60;
+                buf[offset] = if tz < 0 { b'-' } else { b'+' };
+                buf[offset+1] = b'0' + (tz_min / 600) as u8;
+                buf[offset+2] = b'0' + (tz_min / 60 % 10) as u8;
+                buf[offset+3] = b':';
+                buf[offset+4] = b'0' + (tz_min % 60 / 10) as u8;
+                buf[offset+5] = b'0' + (tz_min % 10) as u8;
+                offset + 5
+            }
+        };
+
         // we know our chars are all ascii
-        f.write_str(str::from_utf8(&buf[..=offset]).expect("Conversion to utf8 failed"))
+        f.write_str(str::from_utf8(&buf[..=end]).expect("Conversion to utf8 failed"))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::fmt;
     use std::str::from_utf8;
     use std::time::{UNIX_EPOCH, SystemTime, Duration};
 
@@ -378,6 +1163,12 @@ mod test {
     use super::{parse_rfc3339, parse_rfc3339_weak, format_rfc3339};
     use super::{format_rfc3339_millis, format_rfc3339_micros};
     use super::{format_rfc3339_nanos};
+    use super::{rfc3339_equivalent, parse_rfc3339_trim, approx_eq};
+    use super::{parse_rfc3339_loose, round_to, round_to_nearest};
+    use super::{parse_rfc3339_2digit_year, format_relative_or_absolute};
+    use super::{age_years, days_between, format_date, format_weekday};
+    use super::{timestamp_string, clamp_to_range, time_between, Error};
+    use super::{MIN_YEAR, MAX_YEAR, MAX_TIMESTAMP_SECS};
     use super::max;
 
     fn from_sec(sec: u64) -> (String, SystemTime) {
@@ -425,6 +1216,51 @@ mod test {
             "2012-01-01T00:00:00Z");
     }
 
+    #[test]
+    fn to_string() {
+        for &(secs, nanos) in &[(0, 0), (1_518_563_312, 0),
+                                (1_518_563_312, 123_000_000)] {
+            let time = UNIX_EPOCH + Duration::new(secs, nanos);
+            assert_eq!(timestamp_string(time),
+                       format_rfc3339(time).to_string());
+        }
+    }
+
+    /// Writer into a fixed buffer, to check that formatting never needs
+    /// to allocate
+    struct FixedBuf {
+        buf: [u8; 64],
+        len: usize,
+    }
+
+    impl fmt::Write for FixedBuf {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            if end > self.buf.len() {
+                return Err(fmt::Error);
+            }
+            self.buf[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fixed_buffer() {
+        use std::fmt::Write;
+
+        let time = UNIX_EPOCH + Duration::new(1_518_563_312, 789_456_123);
+        let mut buf = FixedBuf { buf: [0; 64], len: 0 };
+        write!(buf, "{}", crate::Timestamp::from(time)).unwrap();
+        assert_eq!(from_utf8(&buf.buf[..buf.len]).unwrap(),
+                   "2018-02-13T23:08:32.789456123Z");
+
+        let mut buf = FixedBuf { buf: [0; 64], len: 0 };
+        write!(buf, "{}", format_rfc3339_millis(time)).unwrap();
+        assert_eq!(from_utf8(&buf.buf[..buf.len]).unwrap(),
+                   "2018-02-13T23:08:32.789Z");
+    }
+
     #[test]
     fn smoke_tests_format_millis() {
         assert_eq!(
@@ -472,10 +1308,37 @@ mod test {
     #[test]
     fn upper_bound() {
         let max = UNIX_EPOCH + Duration::new(max::SECONDS, 0);
-        assert_eq!(parse_rfc3339(&max::TIMESTAMP).unwrap(), max);
+        assert_eq!(parse_rfc3339(max::TIMESTAMP).unwrap(), max);
         assert_eq!(format_rfc3339(max).to_string(), max::TIMESTAMP);
     }
 
+    #[test]
+    fn year_bounds() {
+        assert_eq!(MAX_TIMESTAMP_SECS, max::SECONDS);
+        parse_rfc3339(&format!("{}-01-01T00:00:00Z", MIN_YEAR)).unwrap();
+        parse_rfc3339(&format!("{}-01-01T00:00:00Z", MAX_YEAR)).unwrap();
+        parse_rfc3339(&format!("{}-01-01T00:00:00Z", MAX_YEAR+1)).unwrap_err();
+        parse_rfc3339(&format!("{}-12-31T23:59:59Z", MIN_YEAR-1)).unwrap_err();
+    }
+
+    #[test]
+    fn clamp() {
+        let max = UNIX_EPOCH + Duration::new(max::SECONDS, 999_999_999);
+        assert_eq!(clamp_to_range(UNIX_EPOCH - Duration::new(1, 0)),
+                   UNIX_EPOCH);
+        assert_eq!(clamp_to_range(UNIX_EPOCH), UNIX_EPOCH);
+        assert_eq!(clamp_to_range(max), max);
+        assert_eq!(format_rfc3339_nanos(clamp_to_range(max)).to_string(),
+                   max::TIMESTAMP.replace("Z", ".999999999Z"));
+        let time = UNIX_EPOCH + Duration::new(1_518_563_312, 0);
+        assert_eq!(clamp_to_range(time), time);
+        if let Some(future) = max.checked_add(Duration::new(1_000_000, 0)) {
+            assert_eq!(clamp_to_range(future), max);
+            assert_eq!(format_rfc3339(clamp_to_range(future)).to_string(),
+                       max::TIMESTAMP.replace("Z", ".999999999Z"));
+        }
+    }
+
     #[test]
     fn leap_second() {
         assert_eq!(parse_rfc3339("2016-12-31T23:59:60Z").unwrap(),
@@ -615,10 +1478,513 @@ mod test {
 
         assert_eq!(parse_rfc3339_weak("1970-01-01 00:00:00.000123Z").unwrap(),
                    UNIX_EPOCH + Duration::new(0, 123_000));
-        parse_rfc3339("1970-01-01 00:00:00.000123Z").unwrap_err();
+        assert_eq!(parse_rfc3339("1970-01-01 00:00:00.000123Z").unwrap(),
+                   UNIX_EPOCH + Duration::new(0, 123_000));
 
         assert_eq!(parse_rfc3339_weak("1970-01-01 00:00:00Z").unwrap(),
                    UNIX_EPOCH + Duration::new(0, 0));
-        parse_rfc3339("1970-01-01 00:00:00Z").unwrap_err();
+        assert_eq!(parse_rfc3339("1970-01-01 00:00:00Z").unwrap(),
+                   UNIX_EPOCH + Duration::new(0, 0));
+    }
+
+    #[test]
+    fn strict_separators() {
+        let time = parse_rfc3339("2018-02-14T00:28:07Z");
+        for sep in &["T", "t", " "] {
+            for zone in &["Z", "z", "+00:00"] {
+                let s = format!("2018-02-14{}00:28:07{}", sep, zone);
+                assert_eq!(parse_rfc3339(&s), time, "{}", s);
+            }
+        }
+        assert_eq!(parse_rfc3339("2018-02-14 00:28:07.5z"),
+                   Ok(time.unwrap() + Duration::new(0, 500_000_000)));
+        parse_rfc3339("2018-02-14 00:28:07").unwrap_err();
+        parse_rfc3339("2018-02-14t00:28:07").unwrap_err();
+        parse_rfc3339("2018-02-14x00:28:07Z").unwrap_err();
+        parse_rfc3339("2018-02-14 00:28:07Zz").unwrap_err();
+        parse_rfc3339_weak("2018-02-14t00:28:07").unwrap_err();
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(parse_rfc3339("2018-02-14T03:28:07+03:00").unwrap(),
+                   parse_rfc3339("2018-02-14T00:28:07Z").unwrap());
+        assert_eq!(parse_rfc3339("2018-02-13T19:58:07.5-04:30").unwrap(),
+                   parse_rfc3339("2018-02-14T00:28:07.5Z").unwrap());
+        assert_eq!(parse_rfc3339_weak("2018-02-14 03:28:07+0300").unwrap(),
+                   parse_rfc3339("2018-02-14T00:28:07Z").unwrap());
+        parse_rfc3339("2018-02-14T03:28:07+0300").unwrap_err();
+        parse_rfc3339("2018-02-14T03:28:07+03").unwrap_err();
+        parse_rfc3339("2018-02-14T03:28:07+03:00Z").unwrap_err();
+        assert_eq!(parse_rfc3339("2018-02-14T03:28:07+24:00").unwrap_err(),
+                   super::Error::OutOfRange);
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00+00:01").unwrap_err(),
+                   super::Error::OutOfRange);
+    }
+
+    #[test]
+    fn date() {
+        let at = |secs| format_date(UNIX_EPOCH + Duration::new(secs, 0));
+        assert_eq!(at(0), "1970-01-01");
+        assert_eq!(at(86399), "1970-01-01");
+        assert_eq!(at(86400), "1970-01-02");
+        assert_eq!(at(1_518_568_087), "2018-02-14");
+        assert_eq!(at(951_782_400), "2000-02-29");
+        assert_eq!(at(max::SECONDS), "9999-12-31");
+        for &s in &["2018-02-14T23:59:59.999Z", "2020-12-31T00:00:00Z",
+                    "1999-01-01T12:00:00Z"] {
+            let t = parse_rfc3339(s).unwrap();
+            assert_eq!(format_date(t), &s[..10]);
+            assert!(format_rfc3339(t).to_string().starts_with(&format_date(t)));
+        }
+    }
+
+    #[test]
+    fn weekday() {
+        for &(s, day) in &[
+            ("1970-01-01T00:00:00Z", "Thu"),
+            ("1970-01-04T23:59:59Z", "Sun"),
+            ("1970-01-05T00:00:00Z", "Mon"),
+            ("2000-02-29T12:00:00Z", "Tue"),
+            ("2000-03-01T00:00:00Z", "Wed"),
+            ("2018-02-14T00:28:07Z", "Wed"),
+            ("2018-02-16T00:00:00Z", "Fri"),
+            ("2018-02-17T00:00:00Z", "Sat"),
+            ("2038-01-19T03:14:07Z", "Tue"),
+            ("9999-12-31T23:59:59Z", "Fri"),
+        ] {
+            let t = parse_rfc3339_weak(s).unwrap();
+            assert_eq!(format_weekday(t), day, "{}", s);
+        }
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(days_between("2018-02-14", "2018-02-14"), Ok(0));
+        assert_eq!(days_between("2020-02-28", "2020-03-01"), Ok(2));
+        assert_eq!(days_between("2019-02-28", "2019-03-01"), Ok(1));
+        assert_eq!(days_between("2020-03-01", "2020-02-28"), Ok(-2));
+        assert_eq!(days_between("2019-12-31", "2020-01-01"), Ok(1));
+        assert_eq!(days_between("2020-01-01", "2021-01-01"), Ok(366));
+        assert_eq!(days_between("2021-01-01", "2022-01-01"), Ok(365));
+        assert_eq!(days_between("1970-01-01", "2000-03-01"), Ok(11017));
+        assert_eq!(days_between("2018-02-14T23:59:59.999Z",
+                                "2018-02-15T00:00:00Z"), Ok(1));
+        assert_eq!(days_between("2018-02-14T00:00:00Z",
+                                "2018-02-14T23:59:59.999Z"), Ok(0));
+        assert_eq!(days_between("2018-02-15T01:00:00+03:00",
+                                "2018-02-15"), Ok(1));
+        assert_eq!(days_between("2019-02-29", "2019-03-01"),
+                   Err(Error::OutOfRange));
+        assert_eq!(days_between("2019-02-28", "tomorrow"),
+                   Err(Error::InvalidFormat));
+    }
+
+    #[test]
+    fn age() {
+        let now = parse_rfc3339("2018-02-14T00:28:07Z").unwrap();
+        assert_eq!(age_years("1990-02-13", now), Ok(28));
+        assert_eq!(age_years("1990-02-14", now), Ok(28));
+        assert_eq!(age_years("1990-02-15", now), Ok(27));
+        assert_eq!(age_years("1990-12-31", now), Ok(27));
+        assert_eq!(age_years("1990-02-14T23:59:59Z", now), Ok(28));
+        assert_eq!(age_years("2018-02-14", now), Ok(0));
+        assert_eq!(age_years("2018-02-15", now), Err(Error::OutOfRange));
+        assert_eq!(age_years("1990-02-30", now), Err(Error::OutOfRange));
+        assert_eq!(age_years("1990-02-1x", now), Err(Error::InvalidDigit));
+        // leap day birthday
+        let at = |s| parse_rfc3339(s).unwrap();
+        assert_eq!(age_years("2000-02-29", at("2017-02-28T23:59:59Z")),
+                   Ok(16));
+        assert_eq!(age_years("2000-02-29", at("2017-03-01T00:00:00Z")),
+                   Ok(17));
+        assert_eq!(age_years("2000-02-29", at("2016-02-28T00:00:00Z")),
+                   Ok(15));
+        assert_eq!(age_years("2000-02-29", at("2016-02-29T00:00:00Z")),
+                   Ok(16));
+    }
+
+    #[test]
+    fn relative_or_absolute() {
+        let now = parse_rfc3339("2018-02-14T00:28:07Z").unwrap();
+        let hour = Duration::from_secs(3600);
+        let f = |t| format_relative_or_absolute(t, now, hour);
+        let second = Duration::new(1, 0);
+        assert_eq!(f(now), "0 seconds ago");
+        assert_eq!(f(now - Duration::from_secs(200)), "3 minutes ago");
+        assert_eq!(f(now - hour + second), "59 minutes ago");
+        assert_eq!(f(now - hour), "1 hour ago");
+        assert_eq!(f(now - hour - second), "2018-02-13");
+        assert_eq!(f(now - Duration::new(0, 1)), "1 nanosecond ago");
+        assert_eq!(f(now + Duration::from_secs(90)), "in 1 minute");
+        assert_eq!(f(now + hour), "in 1 hour");
+        assert_eq!(f(now + hour + second), "2018-02-14");
+        assert_eq!(f(now + hour * 24), "2018-02-15");
+    }
+
+    #[test]
+    fn two_digit_year() {
+        assert_eq!(parse_rfc3339_2digit_year("18-02-14T00:28:07Z", 50),
+                   parse_rfc3339("2018-02-14T00:28:07Z"));
+        assert_eq!(parse_rfc3339_2digit_year("85-02-14T00:28:07.5Z", 50),
+                   parse_rfc3339("1985-02-14T00:28:07.5Z"));
+        assert_eq!(parse_rfc3339_2digit_year("50-01-01T00:00:00Z", 50),
+                   parse_rfc3339("2050-01-01T00:00:00Z"));
+        assert_eq!(parse_rfc3339_2digit_year("51-01-01T00:00:00Z", 50),
+                   Err(Error::OutOfRange));
+        assert_eq!(parse_rfc3339_2digit_year("18-02-14T03:28:07+03:00", 0),
+                   Err(Error::OutOfRange));
+        assert_eq!(parse_rfc3339_2digit_year("18-02-14T03:28:07+03:00", 18),
+                   parse_rfc3339("2018-02-14T00:28:07Z"));
+        assert_eq!(parse_rfc3339_2digit_year("2018-02-14T00:28:07Z", 50),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_rfc3339_2digit_year("x8-02-14T00:28:07Z", 50),
+                   Err(Error::InvalidDigit));
+        assert_eq!(parse_rfc3339_2digit_year("", 50),
+                   Err(Error::InvalidFormat));
+    }
+
+    #[test]
+    fn zero_offsets() {
+        for &frac in &["", ".5", ".123456789"] {
+            let z = parse_rfc3339(&format!("2018-02-14T00:28:07{}Z", frac));
+            for &zone in &["+00:00", "-00:00"] {
+                let s = format!("2018-02-14T00:28:07{}{}", frac, zone);
+                assert_eq!(parse_rfc3339(&s), z, "{}", s);
+            }
+        }
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00-00:00"),
+                   Ok(UNIX_EPOCH));
+    }
+
+    #[test]
+    fn trim() {
+        let time = UNIX_EPOCH + Duration::new(1_518_568_087, 0);
+        assert_eq!(parse_rfc3339_trim("2018-02-14T00:28:07Z\n").unwrap(),
+                   time);
+        assert_eq!(parse_rfc3339_trim("2018-02-14T00:28:07Z\r\n").unwrap(),
+                   time);
+        assert_eq!(parse_rfc3339_trim("2018-02-14T00:28:07Z  ").unwrap(),
+                   time);
+        assert_eq!(parse_rfc3339_trim("  2018-02-14T00:28:07Z").unwrap(),
+                   time);
+        parse_rfc3339_trim("2018-02-14T00:28:07 Z").unwrap_err();
+        parse_rfc3339_trim("2018-02-14 T00:28:07Z").unwrap_err();
+        parse_rfc3339("2018-02-14T00:28:07Z\n").unwrap_err();
+    }
+
+    #[test]
+    fn loose() {
+        let time = UNIX_EPOCH + Duration::new(1_518_568_087, 0);
+        for sep in &["T", "t", " "] {
+            for zone in &["", "Z", "z", "+00:00", "-0000", "+00"] {
+                for (pre, post) in &[("", ""), (" ", "\n"), ("\t", "  ")] {
+                    let s = format!("{}2018-02-14{}00:28:07{}{}",
+                                    pre, sep, zone, post);
+                    assert_eq!(parse_rfc3339_loose(&s), Ok(time), "{:?}", s);
+                }
+            }
+        }
+        assert_eq!(parse_rfc3339_loose("2018-02-14t03:28:07.5+03:00"),
+                   Ok(time + Duration::new(0, 500_000_000)));
+        parse_rfc3339_loose("2018-02-14x00:28:07z").unwrap_err();
+        parse_rfc3339_loose("2018-02-14t00:28:07zz").unwrap_err();
+        parse_rfc3339_loose("2018-02-14 t00:28:07").unwrap_err();
+        parse_rfc3339_loose("").unwrap_err();
+    }
+
+    #[test]
+    fn rounding() {
+        let t = parse_rfc3339("2018-02-14T00:28:37.5Z").unwrap();
+        let ts = |s| parse_rfc3339(s).unwrap();
+        let minute = Duration::from_secs(60);
+        let hour = Duration::from_secs(3600);
+        assert_eq!(round_to(t, minute), ts("2018-02-14T00:28:00Z"));
+        assert_eq!(round_to_nearest(t, minute), ts("2018-02-14T00:29:00Z"));
+        assert_eq!(round_to(t, hour), ts("2018-02-14T00:00:00Z"));
+        assert_eq!(round_to_nearest(t, hour), ts("2018-02-14T00:00:00Z"));
+        assert_eq!(round_to_nearest(ts("2018-02-14T00:30:00Z"), hour),
+                   ts("2018-02-14T01:00:00Z"));
+        assert_eq!(round_to(ts("2018-02-14T01:00:00Z"), hour),
+                   ts("2018-02-14T01:00:00Z"));
+        assert_eq!(round_to(t, Duration::ZERO), t);
+        let before = UNIX_EPOCH - Duration::new(90, 0);
+        assert_eq!(round_to(before, minute), UNIX_EPOCH - minute * 2);
+        assert_eq!(round_to_nearest(before, minute), UNIX_EPOCH - minute);
+    }
+
+    #[test]
+    fn rounding_overflow() {
+        let step = Duration::from_secs(7);
+        let min = UNIX_EPOCH.checked_sub(
+            Duration::new(i64::MAX as u64 + 1, 0));
+        if let Some(min) = min {
+            assert_eq!(round_to(min, step), min);
+            // the closest multiple of 7 seconds is one second later
+            assert_eq!(round_to_nearest(min, step),
+                       min + Duration::from_secs(1));
+        }
+        let max = UNIX_EPOCH.checked_add(
+            Duration::new(i64::MAX as u64, 999_999_999));
+        if let Some(max) = max {
+            let down = round_to(max, step);
+            assert!(down <= max);
+            assert_eq!(round_to_nearest(max, step), down);
+        }
+    }
+
+    #[test]
+    fn approx() {
+        let a = UNIX_EPOCH + Duration::new(1_518_568_087, 100);
+        let b = UNIX_EPOCH + Duration::new(1_518_568_087, 200);
+        assert!(approx_eq(a, b, Duration::from_micros(1)));
+        assert!(approx_eq(b, a, Duration::from_micros(1)));
+        assert!(approx_eq(a, b, Duration::from_nanos(100)));
+        assert!(!approx_eq(a, b, Duration::from_nanos(10)));
+        assert!(!approx_eq(b, a, Duration::from_nanos(10)));
+        assert!(approx_eq(a, a, Duration::from_nanos(0)));
+    }
+
+    #[test]
+    fn between() {
+        let a = "2018-02-14T00:28:07Z";
+        let b = "2018-02-14T01:30:15.5Z";
+        assert_eq!(time_between(a, b).unwrap(), "1h 2m");
+        assert_eq!(time_between(b, a).unwrap(), "1h 2m");
+        assert_eq!(time_between(a, a).unwrap(), "0s");
+        assert_eq!(time_between(a, "2018-02-14T01:28:08Z").unwrap(), "1h");
+        assert_eq!(time_between(a, "2018-02-14T00:28:08.25Z").unwrap(),
+                   "1s 250ms");
+        assert_eq!(time_between(a, "2019-03-20T01:00:00Z").unwrap(),
+                   "1year 1month");
+        assert_eq!(time_between(a, "2018-02-14"),
+                   Err(Error::InvalidFormat));
+    }
+
+    #[test]
+    fn lengths() {
+        use super::{rfc3339_min_len, RFC3339_MAX_LEN};
+        use super::{Rfc3339Timestamp, Precision};
+        assert_eq!(rfc3339_min_len(), 20);
+        assert_eq!(RFC3339_MAX_LEN, 35);
+        let time = UNIX_EPOCH + Duration::new(1_518_568_087, 0);
+        assert_eq!(format_rfc3339(time).to_string().len(), rfc3339_min_len());
+        let time = UNIX_EPOCH + Duration::new(1_518_568_087, 123_456_789);
+        assert_eq!(format_rfc3339_nanos(time).to_string().len(),
+                   RFC3339_MAX_LEN - "+00:00".len() + "Z".len());
+        let fmt = Rfc3339Timestamp(time, Precision::Nanos, Some(-3600), b'T');
+        assert_eq!(fmt.to_string().len(), RFC3339_MAX_LEN);
+        parse_rfc3339(&"2018-02-14T00:28:07Z"[..rfc3339_min_len() - 1])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn time_of_day() {
+        use super::parse_time_of_day;
+        assert_eq!(parse_time_of_day("00:00"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse_time_of_day("14:30"), Ok(Duration::new(52200, 0)));
+        assert_eq!(parse_time_of_day("23:59:59"),
+                   Ok(Duration::new(86399, 0)));
+        assert_eq!(parse_time_of_day("08:00:01.25"),
+                   Ok(Duration::new(28801, 250_000_000)));
+        assert_eq!(parse_time_of_day("noon"), Ok(Duration::new(43200, 0)));
+        assert_eq!(parse_time_of_day("midnight"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse_time_of_day("Noon"), Ok(Duration::new(43200, 0)));
+        assert_eq!(parse_time_of_day("MIDNIGHT"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse_time_of_day("24:00"), Err(Error::OutOfRange));
+        assert_eq!(parse_time_of_day("12:60"), Err(Error::OutOfRange));
+        assert_eq!(parse_time_of_day("1:30"), Err(Error::InvalidFormat));
+        assert_eq!(parse_time_of_day("12:30:1"), Err(Error::InvalidFormat));
+        assert_eq!(parse_time_of_day("12:30:00Z"), Err(Error::InvalidFormat));
+        assert_eq!(parse_time_of_day("12:3x"), Err(Error::InvalidDigit));
+        assert_eq!(parse_time_of_day("noonish"), Err(Error::InvalidFormat));
+    }
+
+    #[test]
+    fn srt_timecode() {
+        use super::parse_srt_timecode;
+        assert_eq!(parse_srt_timecode("00:01:30,500"),
+                   Ok(Duration::new(90, 500_000_000)));
+        assert_eq!(parse_srt_timecode("00:00:00,000"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse_srt_timecode("01:02:03,004"),
+                   Ok(Duration::new(3723, 4_000_000)));
+        assert_eq!(parse_srt_timecode("99:59:59,999"),
+                   Ok(Duration::new(359_999, 999_000_000)));
+        assert_eq!(parse_srt_timecode("00:01:30.500"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_srt_timecode("0:01:30,500"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_srt_timecode("00:01:30,50"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_srt_timecode("00:01:30,5000"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_srt_timecode("00:60:30,500"),
+                   Err(Error::OutOfRange));
+        assert_eq!(parse_srt_timecode("00:01:3x,500"),
+                   Err(Error::InvalidDigit));
+        assert_eq!(parse_srt_timecode("00:01:30,50x"),
+                   Err(Error::InvalidDigit));
+    }
+
+    #[test]
+    fn vtt_timecode() {
+        use super::parse_vtt_timecode;
+        assert_eq!(parse_vtt_timecode("00:01:30.500"),
+                   Ok(Duration::new(90, 500_000_000)));
+        assert_eq!(parse_vtt_timecode("01:30.500"),
+                   Ok(Duration::new(90, 500_000_000)));
+        assert_eq!(parse_vtt_timecode("02:01:30.005"),
+                   Ok(Duration::new(7290, 5_000_000)));
+        assert_eq!(parse_vtt_timecode("123:00:00.000"),
+                   Ok(Duration::new(123 * 3600, 0)));
+        assert_eq!(parse_vtt_timecode("00:00:01:30.500"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_vtt_timecode("0x:01:30.500"),
+                   Err(Error::InvalidDigit));
+        assert_eq!(parse_vtt_timecode("30.500"), Err(Error::InvalidFormat));
+        assert_eq!(parse_vtt_timecode("1:01:30.500"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_vtt_timecode("00:01:30,500"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_vtt_timecode("00:01:30.50"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_vtt_timecode("00:61:30.500"),
+                   Err(Error::OutOfRange));
+        assert_eq!(parse_vtt_timecode("99999999999999999999:00:00.000"),
+                   Err(Error::OutOfRange));
+    }
+
+    #[test]
+    fn since_epoch() {
+        use super::parse_rfc3339_since_epoch;
+        for s in &[
+            "1970-01-01T00:00:00Z",
+            "2018-02-14T00:28:07Z",
+            "2018-02-14T00:28:07.123456789Z",
+            "2018-02-14T03:28:07+03:00",
+            max::TIMESTAMP,
+        ] {
+            let two_step = parse_rfc3339(s).unwrap()
+                .duration_since(UNIX_EPOCH).unwrap();
+            assert_eq!(parse_rfc3339_since_epoch(s), Ok(two_step));
+        }
+        assert_eq!(parse_rfc3339_since_epoch("2018-02-14 00:28:07"),
+                   Err(Error::InvalidFormat));
+        assert_eq!(parse_rfc3339_since_epoch("1969-12-31T23:59:59Z"),
+                   Err(Error::OutOfRange));
+    }
+
+    #[test]
+    fn doubled_zone() {
+        for s in &[
+            "2018-02-14T00:28:07Z+00:00",
+            "2018-02-14T00:28:07.5Z+00:00",
+            "2018-02-14T00:28:07+00:00Z",
+            "2018-02-14T00:28:07.5+00:00Z",
+            "2018-02-14 00:28:07Z+0000",
+            "2018-02-14T00:28:07ZZ",
+        ] {
+            assert_eq!(parse_rfc3339_weak(s), Err(Error::InvalidFormat));
+            assert_eq!(parse_rfc3339(s), Err(Error::InvalidFormat));
+        }
+    }
+
+    #[test]
+    fn weak_offsets() {
+        let utc = parse_rfc3339("2018-02-14T00:28:07Z").unwrap();
+        assert_eq!(parse_rfc3339_weak("2018-02-14T05:28:07+05").unwrap(), utc);
+        assert_eq!(parse_rfc3339_weak("2018-02-14T05:58:07+0530").unwrap(),
+                   utc);
+        assert_eq!(parse_rfc3339_weak("2018-02-14T05:58:07+05:30").unwrap(),
+                   utc);
+        assert_eq!(parse_rfc3339_weak("2018-02-13T19:28:07-05").unwrap(), utc);
+        assert_eq!(parse_rfc3339_weak("2018-02-14T05:28:07+5").unwrap_err(),
+                   super::Error::InvalidFormat);
+        assert_eq!(parse_rfc3339_weak("2018-02-14T05:28:07+053").unwrap_err(),
+                   super::Error::InvalidFormat);
+        parse_rfc3339_weak("2018-02-14T05:28:07+05:3").unwrap_err();
+        parse_rfc3339_weak("2018-02-14T05:28:07+5:30").unwrap_err();
+        assert_eq!(parse_rfc3339_weak("2018-02-14T05:28:07+0x").unwrap_err(),
+                   super::Error::InvalidDigit);
+        parse_rfc3339("2018-02-14T05:28:07+05").unwrap_err();
+    }
+
+    #[test]
+    fn equivalent() {
+        assert!(rfc3339_equivalent("2018-02-14T00:28:07Z",
+                                   "2018-02-14T00:28:07+00:00").unwrap());
+        assert!(rfc3339_equivalent("2018-02-14T00:28:07Z",
+                                   "2018-02-14T00:28:07+0000").unwrap());
+        assert!(rfc3339_equivalent("2018-02-14T00:28:07.100Z",
+                                   "2018-02-14T00:28:07.1+00:00").unwrap());
+        assert!(!rfc3339_equivalent("2018-02-14T00:28:07Z",
+                                    "2018-02-14T00:28:07+01:00").unwrap());
+        assert_eq!(rfc3339_equivalent("2018-02-14T00:28:07Z", "yesterday"),
+                   Err(super::Error::InvalidFormat));
+    }
+
+    #[test]
+    fn space_separator() {
+        use super::{format_rfc3339_space, Precision};
+        let time = UNIX_EPOCH + Duration::new(1_518_568_087, 123_456_789);
+        let fmt = format_rfc3339_space(time, Precision::Seconds).to_string();
+        assert_eq!(fmt, "2018-02-14 00:28:07Z");
+        assert_eq!(parse_rfc3339_weak(&fmt).unwrap(),
+                   UNIX_EPOCH + Duration::new(1_518_568_087, 0));
+        for &precision in &[Precision::Smart, Precision::Nanos] {
+            let fmt = format_rfc3339_space(time, precision).to_string();
+            assert_eq!(fmt, "2018-02-14 00:28:07.123456789Z");
+            assert_eq!(parse_rfc3339_weak(&fmt).unwrap(), time);
+        }
+        assert_eq!(format_rfc3339_space(time, Precision::Micros).to_string(),
+                   "2018-02-14 00:28:07.123456Z");
+        assert_eq!(parse_rfc3339(&format_rfc3339_space(time, Precision::Smart)
+                                 .to_string()).unwrap(), time);
+    }
+
+    #[test]
+    fn offset_format() {
+        use super::{Rfc3339Timestamp, Precision};
+        let time = UNIX_EPOCH + Duration::new(1_518_563_312, 123_000_000);
+        let fmt = Rfc3339Timestamp(time, Precision::Smart, Some(19800), b'T');
+        assert_eq!(fmt.to_string(), "2018-02-14T04:38:32.123000000+05:30");
+        let fmt = Rfc3339Timestamp(time, Precision::Seconds, Some(-3600), b'T');
+        assert_eq!(fmt.to_string(), "2018-02-13T22:08:32-01:00");
+        let fmt = Rfc3339Timestamp(UNIX_EPOCH, Precision::Seconds,
+                                   Some(-60), b'T');
+        assert_eq!(fmt.to_string(), "1970-01-01T00:00:00Z");
+        let fmt = Rfc3339Timestamp(UNIX_EPOCH + Duration::new(3600, 0),
+                                   Precision::Seconds, Some(-18000), b'T');
+        assert_eq!(fmt.to_string(), "1970-01-01T01:00:00Z");
+        let fmt = Rfc3339Timestamp(time, Precision::Seconds, Some(19830), b'T');
+        assert_eq!(fmt.to_string(), "2018-02-14T04:39:32+05:31");
+    }
+
+    #[test]
+    fn offset_seconds_roundtrip() {
+        use super::{Rfc3339Timestamp, Precision};
+        let time = UNIX_EPOCH + Duration::new(1_518_563_312, 0);
+        // local mean time of New York, used before 1883
+        let fmt = Rfc3339Timestamp(time, Precision::Smart, Some(-17762), b'T');
+        assert_eq!(fmt.to_string(), "2018-02-13T18:12:32-04:56");
+        assert_eq!(parse_rfc3339(&fmt.to_string()).unwrap(), time);
+        let fmt = Rfc3339Timestamp(time, Precision::Smart, Some(3629), b'T');
+        assert_eq!(fmt.to_string(), "2018-02-14T00:08:32+01:00");
+        assert_eq!(parse_rfc3339(&fmt.to_string()).unwrap(), time);
+    }
+
+    #[test]
+    #[cfg(feature="localtime")]
+    fn local_roundtrip() {
+        let now = SystemTime::now();
+        let local = super::format_rfc3339_local(now).to_string();
+        assert_eq!(parse_rfc3339(&local).unwrap(), now);
+        let time = UNIX_EPOCH + Duration::new(1_518_563_312, 123_000_000);
+        let local = super::format_rfc3339_local(time).to_string();
+        assert_eq!(parse_rfc3339(&local).unwrap(), time);
+        // negative offsets would put local time before the epoch
+        let time = UNIX_EPOCH + Duration::new(3600, 0);
+        let local = super::format_rfc3339_local(time).to_string();
+        assert_eq!(parse_rfc3339(&local).unwrap(), time);
     }
 }
@@ -1,7 +1,14 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::error::Error as StdError;
 use std::fmt;
+use std::io::{self, BufRead};
+use std::ops::Range;
 use std::str::Chars;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+
+use crate::unit::{unit_by_name, unit_nanos, supported_units, Unit, UnitTable};
+use crate::date::{add_months, clamp_to_range, Error as DateError};
 
 /// Error parsing human-friendly duration
 #[derive(Debug, PartialEq, Clone)]
@@ -46,6 +53,50 @@ pub enum Error {
     NumberOverflow,
     /// The value was an empty string (or consists only whitespace)
     Empty,
+    /// The value is not a valid range of durations
+    ///
+    /// Either the `..` separator is missing or the lower bound is larger
+    /// than the upper one.
+    InvalidRange,
+    /// Entry of a list is not in `key=value` form or the key is empty
+    ///
+    /// The field is a byte offset of the entry in the string.
+    KeyValueExpected(usize),
+    /// Sign (`+` or `-`) after the first time span, like in `5min -3s`
+    ///
+    /// Sign of individual time spans can't be changed. The field is a byte
+    /// offset of the sign in the string.
+    UnexpectedSign(usize),
+    /// The value is not a whole number of the unit
+    ///
+    /// See [`parse_duration_multiple_of`](parse_duration_multiple_of).
+    NotMultiple(Unit),
+    /// There are more time spans than allowed
+    ///
+    /// See [`DurationParser::max_spans`](DurationParser::max_spans).
+    TooManySpans,
+    /// The value has a fractional number of seconds
+    ///
+    /// See [`parse_duration_whole_seconds`](parse_duration_whole_seconds).
+    SubsecondNotAllowed,
+    /// Number looks like hexadecimal, like `0x10s`
+    ///
+    /// Only decimal numbers are supported. The field is a byte offset of
+    /// the `0x` prefix in the string.
+    UnexpectedHex(usize),
+}
+
+/// Category of the duration parsing error, see [`Error::kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Malformed input, or a value that isn't valid in this position
+    Syntax,
+    /// Unknown (or missing) time unit
+    Unknown,
+    /// Value or number of time spans is too large
+    Overflow,
+    /// Value is empty
+    Empty,
 }
 
 impl StdError for Error {}
@@ -72,13 +123,223 @@ impl fmt::Display for Error {
             }
             Error::NumberOverflow => write!(f, "number is too large"),
             Error::Empty => write!(f, "value was empty"),
+            Error::InvalidRange => {
+                write!(f, "invalid range, expected `low..high` \
+                    where low is not larger than high")
+            }
+            Error::KeyValueExpected(offset) => {
+                write!(f, "expected `key=duration` at {}", offset)
+            }
+            Error::UnexpectedSign(offset) => {
+                write!(f, "unexpected sign at {}, \
+                    time spans can't be subtracted", offset)
+            }
+            Error::NotMultiple(unit) => {
+                let name = supported_units().iter()
+                    .find(|&&(_, u)| u == *unit)
+                    .map(|&(name, _)| name)
+                    .unwrap_or("units");
+                write!(f, "value must be a whole number of {}", name)
+            }
+            Error::TooManySpans => write!(f, "too many time spans"),
+            Error::SubsecondNotAllowed => {
+                write!(f, "value must be a whole number of seconds")
+            }
+            Error::UnexpectedHex(offset) => {
+                write!(f, "hexadecimal number at {}, \
+                    only decimal numbers are supported", offset)
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Returns category of the error
+    ///
+    /// This is useful to group errors, e.g. for metrics, without matching
+    /// every variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use humantime::{parse_duration, DurationErrorKind};
+    ///
+    /// let err = parse_duration("10 nights").unwrap_err();
+    /// assert_eq!(err.kind(), DurationErrorKind::Unknown);
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::InvalidCharacter(_) | Error::NumberExpected(_) |
+            Error::InvalidRange | Error::KeyValueExpected(_) |
+            Error::UnexpectedSign(_) | Error::NotMultiple(_) |
+            Error::SubsecondNotAllowed | Error::UnexpectedHex(_)
+            => ErrorKind::Syntax,
+            Error::UnknownUnit { .. } => ErrorKind::Unknown,
+            Error::NumberOverflow | Error::TooManySpans => ErrorKind::Overflow,
+            Error::Empty => ErrorKind::Empty,
+        }
+    }
+
+    /// Returns labeled spans of the source string that caused the error
+    ///
+    /// The `src` must be the string that was parsed. Ranges are byte
+    /// offsets, suitable for diagnostic crates like `miette` or `ariadne`.
+    /// Errors that don't point to a specific place, like
+    /// [`NumberOverflow`](Error::NumberOverflow), return no labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use humantime::parse_duration;
+    ///
+    /// let src = "10 nights";
+    /// let err = parse_duration(src).unwrap_err();
+    /// let labels = err.labels(src);
+    /// assert_eq!(&src[labels[0].0.clone()], "nights");
+    /// ```
+    pub fn labels(&self, src: &str) -> Vec<(Range<usize>, String)> {
+        let char_at = |off: usize| {
+            let len = src.get(off..)
+                .and_then(|s| s.chars().next())
+                .map(|c| c.len_utf8())
+                .unwrap_or(0);
+            off..off + len
+        };
+        let label = match self {
+            Error::InvalidCharacter(off) => {
+                (char_at(*off), "invalid character".to_string())
+            }
+            Error::NumberExpected(off) => {
+                (char_at(*off), "expected number".to_string())
+            }
+            Error::UnknownUnit { start, end, value, .. } if start == end => {
+                (*start..*end,
+                 format!("time unit needed, for example {}sec", value))
+            }
+            Error::UnknownUnit { start, end, .. } => {
+                (*start..*end, "unknown time unit".to_string())
+            }
+            Error::KeyValueExpected(off) => {
+                let end = src.get(*off..)
+                    .and_then(|s| s.find(','))
+                    .map(|pos| off + pos)
+                    .unwrap_or(src.len());
+                (*off..end, "expected `key=duration`".to_string())
+            }
+            Error::UnexpectedSign(off) => {
+                (char_at(*off), "unexpected sign".to_string())
+            }
+            Error::UnexpectedHex(off) => {
+                (*off..*off + 2, "hexadecimal numbers are not supported"
+                    .to_string())
+            }
+            Error::NumberOverflow | Error::Empty | Error::InvalidRange |
+            Error::NotMultiple(_) | Error::TooManySpans |
+            Error::SubsecondNotAllowed => {
+                return Vec::new();
+            }
+        };
+        vec![label]
+    }
+
+    /// Returns displayable error with the offending part of the source
+    ///
+    /// Renders the first of [`labels`](Error::labels) with the slice of
+    /// `src` it points to and its byte range, like
+    /// `unknown time unit "xyz" at 3..6`. Errors without labels are
+    /// displayed as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use humantime::parse_duration;
+    ///
+    /// let src = "10 nights";
+    /// let err = parse_duration(src).unwrap_err();
+    /// assert_eq!(err.display_with_source(src).to_string(),
+    ///            "unknown time unit \"nights\" at 3..9");
+    /// ```
+    pub fn display_with_source<'a>(&'a self, src: &'a str)
+        -> impl fmt::Display + 'a
+    {
+        WithSource { error: self, src }
+    }
+
+    /// Shifts offsets in the error by `delta`
+    ///
+    /// Used when a duration is parsed from a slice of a larger string.
+    pub(crate) fn offset_by(self, delta: usize) -> Error {
+        self.map_offsets(|off| off + delta)
+    }
+
+    /// Converts offsets in the error using the function
+    ///
+    /// Used when a duration is parsed from a modified copy of the string.
+    pub(crate) fn map_offsets<F: Fn(usize) -> usize>(self, f: F) -> Error {
+        match self {
+            Error::InvalidCharacter(off) => Error::InvalidCharacter(f(off)),
+            Error::NumberExpected(off) => Error::NumberExpected(f(off)),
+            Error::KeyValueExpected(off) => Error::KeyValueExpected(f(off)),
+            Error::UnexpectedSign(off) => Error::UnexpectedSign(f(off)),
+            Error::UnexpectedHex(off) => Error::UnexpectedHex(f(off)),
+            Error::UnknownUnit { start, end, unit, value } => {
+                Error::UnknownUnit {
+                    start: f(start),
+                    end: f(end),
+                    unit,
+                    value,
+                }
+            }
+            e => e,
+        }
+    }
+}
+
+struct WithSource<'a> {
+    error: &'a Error,
+    src: &'a str,
+}
+
+impl fmt::Display for WithSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let labels = self.error.labels(self.src);
+        match labels.first() {
+            Some((range, message)) if range.start < range.end => {
+                write!(f, "{} {:?} at {}..{}", message,
+                       &self.src[range.clone()], range.start, range.end)
+            }
+            Some((range, message)) => {
+                write!(f, "{} at {}", message, range.start)
+            }
+            None => self.error.fmt(f),
         }
     }
 }
 
 /// A wrapper type that allows you to Display a Duration
 #[derive(Debug, Clone)]
-pub struct FormattedDuration(Duration);
+pub struct FormattedDuration(Duration, Style);
+
+/// Style of a formatted duration, see [`humanize`](humanize)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// Short unit names `1day 2h 3m 4s`, as `format_duration` does
+    Short,
+    /// Full unit names `1 day 2 hours 3 minutes 4 seconds`
+    Long,
+    /// Hours, minutes and seconds `26:03:04`, hours include days
+    Clock,
+    /// Full unit names joined for prose `1 day, 2 hours and 3 minutes`
+    Prose,
+    /// Days and hours only `5d 3h`, the rest is truncated
+    DaysHours,
+}
+
+/// Fractional digits after this one are ignored
+pub(crate) const MAX_FRACTION_DIGITS: u32 = 18;
+
+/// Exponents are saturated at this value
+const MAX_EXPONENT: i32 = 1000;
 
 trait OverflowOp: Sized {
     fn mul(self, other: Self) -> Result<Self, Error>;
@@ -98,53 +359,240 @@ struct Parser<'a> {
     iter: Chars<'a>,
     src: &'a str,
     current: (u64, u64),
+    errors: Option<Vec<Error>>,
+    rounding: Rounding,
+    max_unit: Option<Unit>,
+    spans: usize,
+    scientific: bool,
+    months: Option<i64>,
+    implicit_one: bool,
+    units: Option<&'a UnitTable>,
+    total_nanos: Option<u128>,
+    si_prefixes: bool,
+    max_spans: Option<usize>,
+    counts: Option<BTreeMap<Unit, u64>>,
+    default_unit: Option<Unit>,
+    ordinals: bool,
 }
 
 impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Parser<'a> {
+        Parser {
+            iter: src.chars(),
+            src,
+            current: (0, 0),
+            errors: None,
+            rounding: Rounding::Truncate,
+            max_unit: None,
+            spans: 0,
+            scientific: false,
+            months: None,
+            implicit_one: false,
+            units: None,
+            total_nanos: None,
+            si_prefixes: false,
+            max_spans: None,
+            counts: None,
+            default_unit: None,
+            ordinals: false,
+        }
+    }
+
     fn off(&self) -> usize {
         self.src.len() - self.iter.as_str().len()
     }
 
+    fn peek(&self) -> Option<char> {
+        self.iter.clone().next()
+    }
+
+    /// Checks whether `src[start..end]` is an ordinal suffix like in `5th`
+    fn is_ordinal(&self, frac: (u64, u32), start: usize, end: usize) -> bool {
+        let name = &self.src[start..end];
+        self.ordinals && frac.1 == 0 &&
+            self.src[..start].ends_with(|c: char| c.is_ascii_digit()) &&
+            matches!(name, "st" | "nd" | "rd" | "th") &&
+            self.units.and_then(|table| table.get(name)).is_none()
+    }
+
+    /// Checks whether `x` at `off` follows a lone zero and precedes a hex
+    /// digit, like in `0x10`
+    fn is_hex_prefix(&self, off: usize) -> bool {
+        let before = &self.src[..off];
+        before.ends_with('0') &&
+            !before[..off - 1].ends_with(|c: char| c.is_ascii_digit() ||
+                                                  c == '.') &&
+            matches!(self.peek(), Some(c) if c.is_ascii_hexdigit())
+    }
+
+    /// Records the error if collecting errors, otherwise returns it
+    fn recover(&mut self, err: Error) -> Result<(), Error> {
+        match self.errors {
+            Some(ref mut errors) => {
+                errors.push(err);
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Skips characters up to the next whitespace or digit
+    fn skip_word(&mut self) {
+        loop {
+            let mut iter = self.iter.clone();
+            match iter.next() {
+                Some(c) if c.is_whitespace() || c.is_ascii_digit() => break,
+                Some(_) => self.iter = iter,
+                None => break,
+            }
+        }
+    }
+
+    /// Checks whether exponent digits (possibly with sign) follow
+    fn exponent_follows(&self) -> bool {
+        let mut iter = self.iter.clone();
+        match iter.next() {
+            Some('+') | Some('-') => iter.next(),
+            c => c,
+        }.map(|c| c.is_ascii_digit()).unwrap_or(false)
+    }
+
+    /// Parses exponent of a number, `e` is already consumed
+    ///
+    /// Absolute value is saturated at `MAX_EXPONENT`, any value that large
+    /// is either an overflow or zero anyway.
+    fn parse_exponent(&mut self) -> i32 {
+        let mut iter = self.iter.clone();
+        let negative = match iter.next() {
+            Some('-') => true,
+            Some('+') => false,
+            _ => {
+                iter = self.iter.clone();
+                false
+            }
+        };
+        let mut exp = 0i32;
+        loop {
+            self.iter = iter.clone();
+            match iter.next() {
+                Some(c @ '0'..='9') => {
+                    exp = (exp * 10 + (c as i32 - '0' as i32)).min(MAX_EXPONENT);
+                }
+                _ => break,
+            }
+        }
+        if negative { -exp } else { exp }
+    }
+
     fn parse_first_char(&mut self) -> Result<Option<u64>, Error> {
-        let off = self.off();
-        for c in self.iter.by_ref() {
+        let mut off = self.off();
+        while let Some(c) = self.iter.next() {
             match c {
                 '0'..='9' => {
                     return Ok(Some(c as u64 - '0' as u64));
                 }
                 c if c.is_whitespace() => continue,
+                'a'..='z' | 'A'..='Z' if self.implicit_one => {
+                    // put the unit back, so it's parsed as usual
+                    self.iter = self.src[self.off() - 1..].chars();
+                    return Ok(Some(1));
+                }
+                '+' | '-' if self.spans > 0 => {
+                    self.recover(Error::UnexpectedSign(self.off() - 1))?;
+                    self.skip_word();
+                    off = self.off();
+                }
                 _ => {
-                    return Err(Error::NumberExpected(off));
+                    self.recover(Error::NumberExpected(off))?;
+                    self.skip_word();
+                    off = self.off();
                 }
             }
         }
         Ok(None)
     }
-    fn parse_unit(&mut self, n: u64, start: usize, end: usize)
+    fn parse_unit(&mut self, n: u64, frac: (u64, u32),
+        start: usize, end: usize)
         -> Result<(), Error>
     {
-        let (mut sec, nsec) = match &self.src[start..end] {
-            "nanos" | "nsec" | "ns" => (0u64, n),
-            "usec" | "us" => (0u64, n.mul(1000)?),
-            "millis" | "msec" | "ms" => (0u64, n.mul(1_000_000)?),
-            "seconds" | "second" | "secs" | "sec" | "s" => (n, 0),
-            "minutes" | "minute" | "min" | "mins" | "m"
-            => (n.mul(60)?, 0),
-            "hours" | "hour" | "hr" | "hrs" | "h" => (n.mul(3600)?, 0),
-            "days" | "day" | "d" => (n.mul(86400)?, 0),
-            "weeks" | "week" | "w" => (n.mul(86400*7)?, 0),
-            "months" | "month" | "M" => (n.mul(2_630_016)?, 0), // 30.44d
-            "years" | "year" | "y" => (n.mul(31_557_600)?, 0), // 365.25d
-            _ => {
-                return Err(Error::UnknownUnit {
+        self.spans += 1;
+        if matches!(self.max_spans, Some(max) if self.spans > max) {
+            return Err(Error::TooManySpans);
+        }
+        let name = &self.src[start..end];
+        if self.si_prefixes && name == "ks" {
+            self.max_unit = self.max_unit.max(Some(Unit::Seconds));
+            return self.add_fixed(n, frac, 1000 * unit_nanos(Unit::Seconds));
+        }
+        let custom = self.units.and_then(|table| table.get(name));
+        let dangling = name.is_empty() && self.src[end..].trim().is_empty();
+        let default = self.default_unit.filter(|_| dangling);
+        let unit = match custom.or_else(|| unit_by_name(name)).or(default) {
+            Some(unit) => unit,
+            None => {
+                return self.recover(Error::UnknownUnit {
                     start, end,
                     unit: self.src[start..end].to_string(),
                     value: n,
                 });
             }
         };
+        self.max_unit = self.max_unit.max(Some(unit));
+        if let Some(ref mut counts) = self.counts {
+            if frac.1 > 0 {
+                let dot = self.src[..start].rfind('.').unwrap_or(start);
+                return Err(Error::InvalidCharacter(dot));
+            }
+            let count = counts.entry(unit).or_insert(0);
+            *count = count.checked_add(n).ok_or(Error::NumberOverflow)?;
+        }
+        if self.months.is_some() && unit >= Unit::Months {
+            return self.add_months(n, frac, unit);
+        }
+        self.add_fixed(n, frac, unit_nanos(unit))
+    }
+
+    /// Adds calendar months, whole months are kept separately
+    ///
+    /// Fractional part that doesn't make up a whole month is added as a
+    /// fixed duration (a month being 30.44 days).
+    fn add_months(&mut self, n: u64, (value, digits): (u64, u32), unit: Unit)
+        -> Result<(), Error>
+    {
+        let mul = if unit == Unit::Years { 12 } else { 1 };
+        let denom = 10u128.pow(digits);
+        let total = (n as u128 * denom + value as u128) * mul;
+        let whole = i64::try_from(total / denom)
+            .map_err(|_| Error::NumberOverflow)?;
+        let months = self.months.unwrap_or(0).checked_add(whole)
+            .ok_or(Error::NumberOverflow)?;
+        self.months = Some(months);
+        let rem = (total % denom) as u64;
+        if rem > 0 {
+            self.add_fixed(0, (rem, digits), unit_nanos(Unit::Months))?;
+        }
+        Ok(())
+    }
+
+    /// Adds fixed duration of `weight` nanoseconds per unit
+    fn add_fixed(&mut self, n: u64, frac: (u64, u32), weight: u128)
+        -> Result<(), Error>
+    {
+        if let Some(total) = self.total_nanos {
+            let nanos = fraction_nanos(n, frac, weight, self.rounding);
+            self.total_nanos = Some(total.checked_add(nanos)
+                .ok_or(Error::NumberOverflow)?);
+            return Ok(());
+        }
+        let (mut sec, nsec) = if frac.1 == 0 {
+            let sec_mul = (weight / 1_000_000_000) as u64;
+            let nsec_mul = (weight % 1_000_000_000) as u64;
+            (n.mul(sec_mul)?, n.mul(nsec_mul)?)
+        } else {
+            fraction(n, frac, weight, self.rounding)?
+        };
         let mut nsec = self.current.1.add(nsec)?;
-        if nsec > 1_000_000_000 {
+        if nsec >= 1_000_000_000 {
             sec = sec.add(nsec / 1_000_000_000)?;
             nsec %= 1_000_000_000;
         }
@@ -153,45 +601,108 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
-    fn parse(mut self) -> Result<Duration, Error> {
+    fn parse(&mut self) -> Result<Duration, Error> {
+        if self.src.trim() == "0" {
+            return Ok(Duration::ZERO);
+        }
         let mut n = self.parse_first_char()?.ok_or(Error::Empty)?;
         'outer: loop {
+            let mut frac = None;
+            // offset right after the dot until a fractional digit is seen
+            let mut dot = None;
+            let mut after_digit = true;
+            let mut exponent_done = false;
             let mut off = self.off();
             while let Some(c) = self.iter.next() {
-                match c {
-                    '0'..='9' => {
+                match (c, &mut frac) {
+                    ('0'..='9', _) | ('.', _) if exponent_done => {
+                        return Err(Error::InvalidCharacter(off));
+                    }
+                    ('0'..='9', None) => {
                         n = n.checked_mul(10)
                             .and_then(|x| x.checked_add(c as u64 - '0' as u64))
                             .ok_or(Error::NumberOverflow)?;
                     }
-                    c if c.is_whitespace() => {}
-                    'a'..='z' | 'A'..='Z' => {
+                    ('0'..='9', Some((value, digits))) => {
+                        if *digits < MAX_FRACTION_DIGITS {
+                            *value = *value * 10 + (c as u64 - '0' as u64);
+                            *digits += 1;
+                        }
+                        dot = None;
+                    }
+                    ('.', None) => {
+                        frac = Some((0, 0));
+                        dot = Some(self.off());
+                    }
+                    (c, _) if c.is_whitespace() => {}
+                    ('e', _) | ('E', _)
+                    if self.scientific && after_digit && self.exponent_follows()
+                    => {
+                        let exp = self.parse_exponent();
+                        let (int, f) = exponent(n, frac.unwrap_or((0, 0)), exp)?;
+                        n = int;
+                        frac = Some(f);
+                        exponent_done = true;
+                    }
+                    ('x', None) | ('X', None) if self.is_hex_prefix(off) => {
+                        return Err(Error::UnexpectedHex(off - 1));
+                    }
+                    ('a'..='z', _) | ('A'..='Z', _) => {
                         break;
                     }
                     _ => {
                         return Err(Error::InvalidCharacter(off));
                     }
                 }
+                after_digit = c.is_ascii_digit();
                 off = self.off();
             }
-            let start = off;
+            if let Some(dot) = dot {
+                return Err(Error::NumberExpected(dot));
+            }
+            let frac = frac.unwrap_or((0, 0));
+            let mut start = off;
             let mut off = self.off();
-            while let Some(c) = self.iter.next() {
-                match c {
-                    '0'..='9' => {
-                        self.parse_unit(n, start, off)?;
-                        n = c as u64 - '0' as u64;
-                        continue 'outer;
-                    }
-                    c if c.is_whitespace() => break,
-                    'a'..='z' | 'A'..='Z' => {}
-                    _ => {
-                        return Err(Error::InvalidCharacter(off));
+            let mut ordinal_skipped = false;
+            loop {
+                while let Some(c) = self.iter.next() {
+                    match c {
+                        '0'..='9' => {
+                            self.parse_unit(n, frac, start, off)?;
+                            n = c as u64 - '0' as u64;
+                            continue 'outer;
+                        }
+                        c if c.is_whitespace() => break,
+                        'a'..='z' | 'A'..='Z' => {}
+                        '+' | '-' => {
+                            self.recover(Error::UnexpectedSign(off))?;
+                            self.parse_unit(n, frac, start, off)?;
+                            self.skip_word();
+                            n = match self.parse_first_char()? {
+                                Some(n) => n,
+                                None => return Ok(Duration::new(
+                                    self.current.0, self.current.1 as u32)),
+                            };
+                            continue 'outer;
+                        }
+                        _ => {
+                            return Err(Error::InvalidCharacter(off));
+                        }
                     }
+                    off = self.off();
                 }
-                off = self.off();
+                if ordinal_skipped || !self.is_ordinal(frac, start, off) {
+                    break;
+                }
+                // the unit follows the suffix, as in `5th day`
+                while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+                    self.iter.next();
+                }
+                ordinal_skipped = true;
+                start = self.off();
+                off = start;
             }
-            self.parse_unit(n, start, off)?;
+            self.parse_unit(n, frac, start, off)?;
             n = match self.parse_first_char()? {
                 Some(n) => n,
                 None => return Ok(
@@ -200,16 +711,178 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parses spans where unit precedes the number, `min 5 sec 30`
+    fn parse_unit_first(&mut self) -> Result<Duration, Error> {
+        loop {
+            while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+                self.iter.next();
+            }
+            let start = self.off();
+            match self.peek() {
+                None if self.spans == 0 => return Err(Error::Empty),
+                None => return Ok(
+                    Duration::new(self.current.0, self.current.1 as u32)),
+                Some(c) if c.is_ascii_alphabetic() => {}
+                Some(_) => return Err(Error::InvalidCharacter(start)),
+            }
+            while matches!(self.peek(), Some(c) if c.is_ascii_alphabetic()) {
+                self.iter.next();
+            }
+            let end = self.off();
+            while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+                self.iter.next();
+            }
+            let mut n: Option<u64> = None;
+            let mut frac = None;
+            let mut off = self.off();
+            while let Some(c) = self.peek() {
+                match (c, &mut frac) {
+                    ('0'..='9', None) => {
+                        n = n.unwrap_or(0).checked_mul(10)
+                            .and_then(|x| x.checked_add(c as u64 - '0' as u64))
+                            .map(Some)
+                            .ok_or(Error::NumberOverflow)?;
+                    }
+                    ('0'..='9', Some((value, digits))) => {
+                        if *digits < MAX_FRACTION_DIGITS {
+                            *value = *value * 10 + (c as u64 - '0' as u64);
+                            *digits += 1;
+                        }
+                    }
+                    ('.', None) if n.is_some() => frac = Some((0, 0)),
+                    (c, _) if c.is_whitespace() => break,
+                    _ if n.is_none() => return Err(Error::NumberExpected(off)),
+                    _ => return Err(Error::InvalidCharacter(off)),
+                }
+                self.iter.next();
+                off = self.off();
+            }
+            let n = n.ok_or(Error::NumberExpected(off))?;
+            self.parse_unit(n, frac.unwrap_or((0, 0)), start, end)?;
+        }
+    }
+}
+
+/// Scans a decimal number `12.5` starting at `idx` in ASCII input
+///
+/// Any of `separators` starts the fractional part. Returns the integer
+/// part, the fraction (as in [`fraction`]) and the number of integer
+/// digits, `idx` is moved past the number. Fractional digits after
+/// `MAX_FRACTION_DIGITS` are ignored.
+pub(crate) fn scan_decimal(b: &[u8], idx: &mut usize, separators: &[u8])
+    -> Result<(u64, (u64, u32), usize), Error>
+{
+    let start = *idx;
+    let mut n = 0u64;
+    while *idx < b.len() && b[*idx].is_ascii_digit() {
+        n = n.checked_mul(10)
+            .and_then(|x| x.checked_add((b[*idx] - b'0') as u64))
+            .ok_or(Error::NumberOverflow)?;
+        *idx += 1;
+    }
+    let digits = *idx - start;
+    let mut frac = (0, 0);
+    if *idx < b.len() && separators.contains(&b[*idx]) {
+        *idx += 1;
+        while *idx < b.len() && b[*idx].is_ascii_digit() {
+            if frac.1 < MAX_FRACTION_DIGITS {
+                frac = (frac.0 * 10 + (b[*idx] - b'0') as u64, frac.1 + 1);
+            }
+            *idx += 1;
+        }
+    }
+    Ok((n, frac, digits))
+}
+
+/// Adds seconds and nanoseconds to the total, carrying whole seconds
+pub(crate) fn add_sec_nsec(total: (u64, u64), (sec, nsec): (u64, u64))
+    -> Result<(u64, u64), Error>
+{
+    let nsec = total.1 + nsec;
+    Ok((
+        total.0.checked_add(sec)
+            .and_then(|x| x.checked_add(nsec / 1_000_000_000))
+            .ok_or(Error::NumberOverflow)?,
+        nsec % 1_000_000_000,
+    ))
+}
+
+/// Converts number with fractional part into seconds and nanoseconds
+///
+/// The `weight` is the number of nanoseconds in the unit.
+pub(crate) fn fraction(n: u64, frac: (u64, u32), weight: u128,
+    rounding: Rounding)
+    -> Result<(u64, u64), Error>
+{
+    let nanos = fraction_nanos(n, frac, weight, rounding);
+    let sec = u64::try_from(nanos / 1_000_000_000)
+        .map_err(|_| Error::NumberOverflow)?;
+    Ok((sec, (nanos % 1_000_000_000) as u64))
+}
+
+/// Converts number with fractional part into nanoseconds
+///
+/// Can't overflow as both the number and the fraction are at most 64 bits
+/// and the weight is less than 64 bits too.
+fn fraction_nanos(n: u64, (value, digits): (u64, u32), weight: u128,
+    rounding: Rounding)
+    -> u128
+{
+    let denom = 10u128.pow(digits);
+    let frac_nanos = value as u128 * weight;
+    let mut nanos = n as u128 * weight + frac_nanos / denom;
+    let rem = frac_nanos % denom;
+    let round_up = match rounding {
+        Rounding::Truncate => false,
+        Rounding::Nearest => rem * 2 >= denom,
+        Rounding::Up => rem > 0,
+    };
+    if round_up {
+        nanos += 1;
+    }
+    nanos
+}
+
+/// Applies decimal exponent to a number with fractional part
+fn exponent(n: u64, (value, digits): (u64, u32), exp: i32)
+    -> Result<(u64, (u64, u32)), Error>
+{
+    let pow10 = |k: u32| 10u128.checked_pow(k);
+    // all digits as an integer, fits because there are at most 38 digits
+    let mantissa = n as u128 * 10u128.pow(digits) + value as u128;
+    let scale = exp as i64 - digits as i64;
+    if mantissa == 0 {
+        return Ok((0, (0, 0)));
+    }
+    if scale >= 0 {
+        let int = pow10(scale as u32)
+            .and_then(|p| mantissa.checked_mul(p))
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(Error::NumberOverflow)?;
+        return Ok((int, (0, 0)));
+    }
+    let shift = (-scale) as u32;
+    let int = pow10(shift).map(|p| mantissa / p).unwrap_or(0);
+    let rem = pow10(shift).map(|p| mantissa % p).unwrap_or(mantissa);
+    let digits = shift.min(MAX_FRACTION_DIGITS);
+    let value = pow10(shift - digits).map(|p| rem / p).unwrap_or(0);
+    let int = u64::try_from(int).map_err(|_| Error::NumberOverflow)?;
+    Ok((int, (value as u64, digits)))
 }
 
 /// Parse duration object `1hour 12min 5s`
 ///
 /// The duration object is a concatenation of time spans. Where each time
-/// span is an integer number and a suffix. Supported suffixes:
+/// span is a number and a suffix. The number may have a fractional part
+/// for any unit, e.g. `1.5h` or `0.5y` (which is 182.625 days), see
+/// [`DurationParser`](DurationParser) for rounding of the sub-nanosecond
+/// remainder (it's truncated by default). Supported suffixes:
 ///
-/// * `nsec`, `ns` -- nanoseconds
-/// * `usec`, `us` -- microseconds
-/// * `msec`, `ms` -- milliseconds
+/// * `nanoseconds`, `nanosecond`, `nsec`, `ns` -- nanoseconds
+/// * `microseconds`, `microsecond`, `usec`, `us` -- microseconds
+/// * `milliseconds`, `millisecond`, `msec`, `ms` -- milliseconds
+/// * `cs` -- centiseconds
+/// * `ds` -- deciseconds
 /// * `seconds`, `second`, `sec`, `s`
 /// * `minutes`, `minute`, `min`, `m`
 /// * `hours`, `hour`, `hr`, `h`
@@ -218,6 +891,12 @@ impl<'a> Parser<'a> {
 /// * `months`, `month`, `M` -- defined as 30.44 days
 /// * `years`, `year`, `y` -- defined as 365.25 days
 ///
+/// The same list is available at runtime via
+/// [`supported_units`](crate::supported_units).
+///
+/// As a special case, a lone `0` is accepted without a unit, because zero
+/// is the same in any unit.
+///
 /// # Examples
 ///
 /// ```
@@ -226,81 +905,1253 @@ impl<'a> Parser<'a> {
 ///
 /// assert_eq!(parse_duration("2h 37min"), Ok(Duration::new(9420, 0)));
 /// assert_eq!(parse_duration("32ms"), Ok(Duration::new(0, 32_000_000)));
+/// assert_eq!(parse_duration("0"), Ok(Duration::new(0, 0)));
+/// assert_eq!(parse_duration("1.5s"), Ok(Duration::new(1, 500_000_000)));
 /// ```
 pub fn parse_duration(s: &str) -> Result<Duration, Error> {
-    Parser {
-        iter: s.chars(),
-        src: s,
-        current: (0, 0),
-    }.parse()
+    Parser::new(s).parse()
 }
 
-/// Formats duration into a human-readable string
+/// Parse duration object that may use exponent notation `1e3ms`
 ///
-/// Note: this format is guaranteed to have same value when using
-/// parse_duration, but we can change some details of the exact composition
-/// of the value.
+/// Same as [`parse_duration`](parse_duration), but a number may be
+/// followed by `e` or `E`, optional sign and decimal exponent, like
+/// `1.5e3s` or `5e-1s`. The exponent must follow the digits immediately,
+/// otherwise `e` is treated as a start of a unit.
 ///
 /// # Examples
 ///
 /// ```
 /// use std::time::Duration;
-/// use humantime::format_duration;
+/// use humantime::parse_duration_scientific;
 ///
-/// let val1 = Duration::new(9420, 0);
-/// assert_eq!(format_duration(val1).to_string(), "2h 37m");
-/// let val2 = Duration::new(0, 32_000_000);
-/// assert_eq!(format_duration(val2).to_string(), "32ms");
+/// assert_eq!(parse_duration_scientific("1e3ms"), Ok(Duration::new(1, 0)));
+/// assert_eq!(parse_duration_scientific("2.5e-1s"),
+///            Ok(Duration::new(0, 250_000_000)));
 /// ```
-pub fn format_duration(val: Duration) -> FormattedDuration {
-    FormattedDuration(val)
+pub fn parse_duration_scientific(s: &str) -> Result<Duration, Error> {
+    let mut parser = Parser::new(s);
+    parser.scientific = true;
+    parser.parse()
 }
 
-fn item_plural(f: &mut fmt::Formatter, started: &mut bool,
-    name: &str, value: u64)
-    -> fmt::Result
-{
-    if value > 0 {
-        if *started {
-            f.write_str(" ")?;
-        }
-        write!(f, "{}{}", value, name)?;
-        if value > 1 {
-            f.write_str("s")?;
-        }
-        *started = true;
-    }
-    Ok(())
+/// Parse duration object into total number of nanoseconds
+///
+/// Unlike [`parse_duration`](parse_duration), the value is accumulated in
+/// `u128`, so it may exceed the range of `Duration`. The result is the same
+/// as `parse_duration(s)?.as_nanos()` for values that fit in `Duration`.
+///
+/// Note that only the total is `u128`: each number is still limited to
+/// `u64`, so `18446744073709551616ns` fails with
+/// [`NumberOverflow`](Error::NumberOverflow). Spell large values with
+/// bigger units or several spans (`18446744073709551615ns 1ns`) instead.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::parse_duration_u128_nanos;
+///
+/// assert_eq!(parse_duration_u128_nanos("1s 5ns"), Ok(1_000_000_005));
+/// assert_eq!(parse_duration_u128_nanos("18446744073709551615y"),
+///            Ok(18446744073709551615 * 31_557_600_000_000_000));
+/// ```
+pub fn parse_duration_u128_nanos(s: &str) -> Result<u128, Error> {
+    let mut parser = Parser::new(s);
+    parser.total_nanos = Some(0);
+    parser.parse()?;
+    Ok(parser.total_nanos.unwrap_or(0))
 }
-fn item(f: &mut fmt::Formatter, started: &mut bool, name: &str, value: u32)
-    -> fmt::Result
+
+/// Parse duration object that must be a whole number of the unit
+///
+/// Returns [`NotMultiple`](Error::NotMultiple) otherwise. This is useful
+/// to validate settings like "interval must be a whole number of minutes".
+/// Note that any unit may be used in the string, so `120s` is a whole
+/// number of minutes.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{parse_duration_multiple_of, DurationError, Unit};
+///
+/// assert_eq!(parse_duration_multiple_of("120s", Unit::Minutes),
+///            Ok(Duration::new(120, 0)));
+/// assert_eq!(parse_duration_multiple_of("90s", Unit::Minutes),
+///            Err(DurationError::NotMultiple(Unit::Minutes)));
+/// ```
+pub fn parse_duration_multiple_of(s: &str, unit: Unit)
+    -> Result<Duration, Error>
 {
-    if value > 0 {
-        if *started {
-            f.write_str(" ")?;
-        }
-        write!(f, "{}{}", value, name)?;
-        *started = true;
+    let value = parse_duration(s)?;
+    if value.as_nanos() % unit_nanos(unit) != 0 {
+        return Err(Error::NotMultiple(unit));
     }
-    Ok(())
+    Ok(value)
 }
 
-impl FormattedDuration {
-    /// Returns a reference to the [`Duration`][] that is being formatted.
-    pub fn get_ref(&self) -> &Duration {
-        &self.0
+/// Parse duration object that has no fractional seconds
+///
+/// Returns [`SubsecondNotAllowed`](Error::SubsecondNotAllowed) if the
+/// total has a nonzero nanosecond part. This is useful for schedules that
+/// only support whole seconds. Subsecond units are fine as long as they
+/// add up to whole seconds, so `1500ms 500ms` is accepted.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{parse_duration_whole_seconds, DurationError};
+///
+/// assert_eq!(parse_duration_whole_seconds("5s"), Ok(Duration::new(5, 0)));
+/// assert_eq!(parse_duration_whole_seconds("500ms"),
+///            Err(DurationError::SubsecondNotAllowed));
+/// ```
+pub fn parse_duration_whole_seconds(s: &str) -> Result<Duration, Error> {
+    let value = parse_duration(s)?;
+    if value.subsec_nanos() != 0 {
+        return Err(Error::SubsecondNotAllowed);
     }
+    Ok(value)
 }
 
-impl fmt::Display for FormattedDuration {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let secs = self.0.as_secs();
-        let nanos = self.0.subsec_nanos();
+/// Parse duration object that can be added to the current time
+///
+/// Same as [`parse_duration`](parse_duration), but returns
+/// [`NumberOverflow`](Error::NumberOverflow) if the duration added to
+/// `SystemTime::now()` overflows. Use it to validate timeouts and
+/// deadlines: the limit depends on platform, but it's always at least a few
+/// hundred years.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{parse_duration_schedulable, DurationError};
+///
+/// assert_eq!(parse_duration_schedulable("2h"), Ok(Duration::new(7200, 0)));
+/// assert_eq!(parse_duration_schedulable("18446744073709551615s"),
+///            Err(DurationError::NumberOverflow));
+/// ```
+pub fn parse_duration_schedulable(s: &str) -> Result<Duration, Error> {
+    let value = parse_duration(s)?;
+    SystemTime::now().checked_add(value).ok_or(Error::NumberOverflow)?;
+    Ok(value)
+}
 
-        if secs == 0 && nanos == 0 {
-            f.write_str("0s")?;
-            return Ok(());
+/// Parse duration object returning the quantity of each unit
+///
+/// Quantities of repeated units are summed, so `2h 30min 30min` is two
+/// hours and sixty minutes. This is useful to show the user what was
+/// entered. Fractional quantities are rejected with
+/// [`InvalidCharacter`](Error::InvalidCharacter) pointing to the dot. The
+/// value is otherwise validated as in [`parse_duration`](parse_duration).
+/// A lone `0` has no units, so the result is empty.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{parse_duration_counts, Unit};
+///
+/// let counts = parse_duration_counts("2h 30min 30min").unwrap();
+/// assert_eq!(counts[&Unit::Hours], 2);
+/// assert_eq!(counts[&Unit::Minutes], 60);
+/// ```
+pub fn parse_duration_counts(s: &str) -> Result<BTreeMap<Unit, u64>, Error> {
+    let mut parser = Parser::new(s);
+    parser.counts = Some(BTreeMap::new());
+    parser.parse()?;
+    Ok(parser.counts.unwrap_or_default())
+}
+
+/// Parse duration where the final number may omit the unit `500`
+///
+/// The trailing number without a unit is interpreted in `default` units,
+/// e.g. milliseconds for a latency setting. Only the last number may
+/// lack a unit, so `1s 500` is one and a half seconds with milliseconds by
+/// default. Otherwise the format is the same as in
+/// [`parse_duration`](parse_duration).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{parse_duration_with_default, Unit};
+///
+/// assert_eq!(parse_duration_with_default("500", Unit::Millis),
+///            Ok(Duration::from_millis(500)));
+/// assert_eq!(parse_duration_with_default("5s", Unit::Millis),
+///            Ok(Duration::new(5, 0)));
+/// ```
+pub fn parse_duration_with_default<U: Into<Unit>>(s: &str, default: U)
+    -> Result<Duration, Error>
+{
+    let mut parser = Parser::new(s);
+    parser.default_unit = Some(default.into());
+    parser.parse()
+}
+
+/// Parse percentage of a duration `50% of 2h`
+///
+/// The percentage is a whole number and may be larger than `100`. The
+/// duration after `of` uses format of [`parse_duration`](parse_duration).
+/// The result is truncated to whole nanoseconds.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_percent;
+///
+/// assert_eq!(parse_duration_percent("50% of 2h"),
+///            Ok(Duration::new(3600, 0)));
+/// assert_eq!(parse_duration_percent("200% of 30s"),
+///            Ok(Duration::new(60, 0)));
+/// ```
+pub fn parse_duration_percent(s: &str) -> Result<Duration, Error> {
+    let trimmed = s.trim_start();
+    let start = s.len() - trimmed.len();
+    let digits = trimmed.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return Err(if trimmed.is_empty() {
+            Error::Empty
+        } else {
+            Error::NumberExpected(start)
+        });
+    }
+    let percent: u128 = trimmed[..digits].parse()
+        .map_err(|_| Error::NumberOverflow)?;
+    let mut off = start + digits;
+    if !s[off..].starts_with('%') {
+        return Err(Error::InvalidCharacter(off));
+    }
+    off += 1;
+    let rest = &s[off..];
+    let after_of = rest.trim_start().strip_prefix("of")
+        .filter(|tail| tail.starts_with(char::is_whitespace))
+        .filter(|_| rest.starts_with(char::is_whitespace));
+    let tail = match after_of {
+        Some(tail) => tail,
+        None => {
+            let pos = rest.len() - rest.trim_start().len();
+            return Err(Error::InvalidCharacter(off + pos));
+        }
+    };
+    let base_off = s.len() - tail.len();
+    let base = parse_duration(tail).map_err(|e| e.offset_by(base_off))?;
+    let nanos = base.as_nanos().checked_mul(percent)
+        .ok_or(Error::NumberOverflow)? / 100;
+    let secs = u64::try_from(nanos / 1_000_000_000)
+        .map_err(|_| Error::NumberOverflow)?;
+    Ok(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+/// Parse duration object that must be written in canonical spacing
+///
+/// Same as [`parse_duration`](parse_duration), but the only whitespace
+/// allowed is a single space between time spans, like in `2h 37min`.
+/// Leading, trailing or repeated whitespace, non-space whitespace and
+/// whitespace between a number and its unit are reported as
+/// [`InvalidCharacter`](Error::InvalidCharacter) with the offset of the
+/// offending character. Useful for linting configuration files.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{parse_duration_canonical, DurationError};
+///
+/// assert_eq!(parse_duration_canonical("2h 37min"),
+///            Ok(Duration::new(9420, 0)));
+/// assert_eq!(parse_duration_canonical("2h  37min"),
+///            Err(DurationError::InvalidCharacter(3)));
+/// ```
+pub fn parse_duration_canonical(s: &str) -> Result<Duration, Error> {
+    let mut prev = None;
+    let mut iter = s.char_indices().peekable();
+    while let Some((off, c)) = iter.next() {
+        if c.is_whitespace() {
+            let after_unit = prev.map(|p: char| p.is_alphabetic())
+                .unwrap_or(false);
+            if c != ' ' || !after_unit || iter.peek().is_none() {
+                return Err(Error::InvalidCharacter(off));
+            }
+        }
+        prev = Some(c);
+    }
+    parse_duration(s)
+}
+
+/// Parse duration object where unit precedes the number `min 5 sec 30`
+///
+/// This is for legacy formats only. Each time span is a unit followed by
+/// a number (optionally separated by whitespace), and spans are separated
+/// by whitespace. Units are the same as for
+/// [`parse_duration`](parse_duration). Spans written in the usual order,
+/// like `5min`, are rejected.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_unit_first;
+///
+/// assert_eq!(parse_duration_unit_first("min 5 sec 30"),
+///            Ok(Duration::new(330, 0)));
+/// ```
+pub fn parse_duration_unit_first(s: &str) -> Result<Duration, Error> {
+    Parser::new(s).parse_unit_first()
+}
+
+/// Parse duration at the start of the string, returning the rest
+///
+/// The duration ends at the first `/`, so rate-like values such as
+/// `100ms/op` can be parsed. The returned tail starts with the `/` and is
+/// empty if there is no `/`. The duration part uses format of
+/// [`parse_duration`](parse_duration), and error offsets are relative to
+/// the start of the string.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_prefix;
+///
+/// assert_eq!(parse_duration_prefix("100ms/op"),
+///            Ok((Duration::new(0, 100_000_000), "/op")));
+/// ```
+pub fn parse_duration_prefix(s: &str) -> Result<(Duration, &str), Error> {
+    let end = s.find('/').unwrap_or(s.len());
+    Ok((parse_duration(&s[..end])?, &s[end..]))
+}
+
+/// Parse comma-separated list of named durations `connect=5s,read=30s`
+///
+/// Each value is parsed by [`parse_duration`](parse_duration), whitespace
+/// around keys is ignored. Keys are returned in the same order as in the
+/// input, duplicates are kept. The string that consists only of whitespace
+/// is an empty list.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_map;
+///
+/// assert_eq!(parse_duration_map("connect=5s, read=30s"), Ok(vec![
+///     ("connect".to_string(), Duration::new(5, 0)),
+///     ("read".to_string(), Duration::new(30, 0)),
+/// ]));
+/// ```
+pub fn parse_duration_map(s: &str) -> Result<Vec<(String, Duration)>, Error> {
+    let mut result = Vec::new();
+    if s.trim().is_empty() {
+        return Ok(result);
+    }
+    let mut offset = 0;
+    for entry in s.split(',') {
+        let eq = entry.find('=').ok_or(Error::KeyValueExpected(offset))?;
+        let key = entry[..eq].trim();
+        if key.is_empty() {
+            return Err(Error::KeyValueExpected(offset));
+        }
+        let value = parse_duration(&entry[eq+1..])
+            .map_err(|e| e.offset_by(offset + eq + 1))?;
+        result.push((key.to_string(), value));
+        offset += entry.len() + 1;
+    }
+    Ok(result)
+}
+
+/// Parse duration that may be preceded by a separator `=5s` or `: 30min`
+///
+/// Single `=` or `:` (optionally surrounded by whitespace) is skipped before
+/// the duration, which is convenient for values left after stripping a key.
+/// Otherwise it is the same as [`parse_duration`](parse_duration).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_after_sep;
+///
+/// assert_eq!(parse_duration_after_sep("=5s"), Ok(Duration::new(5, 0)));
+/// assert_eq!(parse_duration_after_sep("5s"), Ok(Duration::new(5, 0)));
+/// ```
+pub fn parse_duration_after_sep(s: &str) -> Result<Duration, Error> {
+    let trimmed = s.trim_start();
+    match trimmed.chars().next() {
+        Some('=') | Some(':') => {
+            let offset = s.len() - trimmed.len() + 1;
+            parse_duration(&s[offset..]).map_err(|e| e.offset_by(offset))
+        }
+        _ => parse_duration(s),
+    }
+}
+
+/// Parse duration with digit group separators `1,000ms`
+///
+/// The `sep` is stripped from numbers, but only if it is placed every three
+/// digits, e.g. `1,000,000`. Misplaced separator returns
+/// [`InvalidCharacter`](Error::InvalidCharacter) pointing to it.
+///
+/// Note: if `sep` is `.` fractional numbers are not supported, because dot
+/// is treated as a group separator.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_grouped;
+///
+/// assert_eq!(parse_duration_grouped("1.000s", '.'), Ok(Duration::new(1000, 0)));
+/// assert_eq!(parse_duration_grouped("1,500ms", ','), Ok(Duration::new(1, 500_000_000)));
+/// ```
+pub fn parse_duration_grouped(s: &str, sep: char) -> Result<Duration, Error> {
+    let mut stripped = String::with_capacity(s.len());
+    // offset in the original string for every byte of the stripped one
+    let mut offsets = Vec::with_capacity(s.len() + 1);
+    // number of digits in the current group, if inside a number
+    let mut group = None;
+    // offset of the last separator in the current number, if any
+    let mut last_sep = None;
+    for (off, c) in s.char_indices() {
+        if c == sep {
+            match (group, last_sep) {
+                (Some(1..=3), None) | (Some(3), Some(_)) => {}
+                _ => return Err(Error::InvalidCharacter(off)),
+            }
+            group = Some(0);
+            last_sep = Some(off);
+            continue;
+        }
+        if c.is_ascii_digit() {
+            group = Some(group.unwrap_or(0) + 1);
+        } else {
+            if let Some(sep_off) = last_sep {
+                if group != Some(3) {
+                    return Err(Error::InvalidCharacter(sep_off));
+                }
+            }
+            group = None;
+            last_sep = None;
+        }
+        stripped.push(c);
+        offsets.extend(off..off + c.len_utf8());
+    }
+    if let Some(sep_off) = last_sep {
+        if group != Some(3) {
+            return Err(Error::InvalidCharacter(sep_off));
+        }
+    }
+    offsets.push(s.len());
+    parse_duration(&stripped).map_err(|e| e.map_offsets(|off| offsets[off]))
+}
+
+/// Rounding of the sub-nanosecond remainder of fractional values
+///
+/// See [`DurationParser::rounding`](DurationParser::rounding).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rounding {
+    /// Drop the remainder (default)
+    #[default]
+    Truncate,
+    /// Round to the nearest nanosecond, half a nanosecond is rounded up
+    Nearest,
+    /// Round up to the next nanosecond if there is any remainder
+    Up,
+}
+
+/// A configurable duration parser
+///
+/// The format is the same as in [`parse_duration`](parse_duration), and
+/// default settings give exactly the same result.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{DurationParser, Rounding};
+///
+/// let mut parser = DurationParser::new();
+/// parser.rounding(Rounding::Up);
+/// assert_eq!(parser.parse("0.0000000001s"), Ok(Duration::new(0, 1)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DurationParser {
+    rounding: Rounding,
+    implicit_one: bool,
+    units: Option<UnitTable>,
+    si_prefixes: bool,
+    max_spans: Option<usize>,
+    ordinals: bool,
+}
+
+impl DurationParser {
+    /// Creates a parser with default settings
+    pub fn new() -> DurationParser {
+        DurationParser::default()
+    }
+
+    /// Sets how sub-nanosecond part of fractional values is rounded
+    ///
+    /// For example, `1.0000000005s` is `1s` when truncated and
+    /// `1s 1ns` when rounded either to nearest or up. Only first 18
+    /// fractional digits are taken into account.
+    pub fn rounding(&mut self, rounding: Rounding) -> &mut Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Allows units without a number, meaning one unit
+    ///
+    /// This is convenient for terse command-line flags, so that `h` is
+    /// one hour and `m s` is one minute and one second. Disabled by
+    /// default.
+    pub fn implicit_one(&mut self, enable: bool) -> &mut Self {
+        self.implicit_one = enable;
+        self
+    }
+
+    /// Sets additional unit names, e.g. in other languages
+    ///
+    /// Names from the table are checked first, then the built-in ones. The
+    /// table is copied into the parser.
+    pub fn units(&mut self, table: &UnitTable) -> &mut Self {
+        self.units = Some(table.clone());
+        self
+    }
+
+    /// Allows SI-prefixed seconds, i.e. `ks` for kiloseconds
+    ///
+    /// Only `ks` (1000 seconds) is supported: other prefixes either clash
+    /// with existing units (`Ms` would read as months, `hs` as hours) or
+    /// are already there (`ms`, `us`, `ns`, `cs`, `ds`). Disabled by
+    /// default.
+    pub fn si_prefixes(&mut self, enable: bool) -> &mut Self {
+        self.si_prefixes = enable;
+        self
+    }
+
+    /// Limits the number of time spans, like `5min` in `1h 5min`
+    ///
+    /// Parsing fails with [`TooManySpans`](Error::TooManySpans) if there
+    /// are more than `max` spans. This is a sanity check for untrusted
+    /// input. Unlimited by default.
+    pub fn max_spans(&mut self, max: usize) -> &mut Self {
+        self.max_spans = Some(max);
+        self
+    }
+
+    /// Allows ordinal suffix after a number, so `5th day` is five days
+    ///
+    /// Suffixes `st`, `nd`, `rd` and `th` are skipped only if they
+    /// immediately follow an integer (not `5 th`) and aren't units in the
+    /// [custom table](DurationParser::units). The suffix isn't checked
+    /// against the number. Disabled by default.
+    pub fn ordinals(&mut self, enable: bool) -> &mut Self {
+        self.ordinals = enable;
+        self
+    }
+
+    /// Parses duration using configured settings
+    pub fn parse(&self, s: &str) -> Result<Duration, Error> {
+        let mut parser = Parser::new(s);
+        parser.rounding = self.rounding;
+        parser.implicit_one = self.implicit_one;
+        parser.units = self.units.as_ref();
+        parser.si_prefixes = self.si_prefixes;
+        parser.max_spans = self.max_spans;
+        parser.ordinals = self.ordinals;
+        parser.parse()
+    }
+}
+
+/// Parse duration object and return the largest unit used in it
+///
+/// This is useful to find out in which units the user thinks, e.g.
+/// `2h 30min` returns `Unit::Hours`. For a lone `0` (which has no unit)
+/// `Unit::Nanos` is returned.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{parse_duration_with_meta, Unit};
+///
+/// assert_eq!(parse_duration_with_meta("2h 30min"),
+///            Ok((Duration::new(9000, 0), Unit::Hours)));
+/// ```
+pub fn parse_duration_with_meta(s: &str) -> Result<(Duration, Unit), Error> {
+    let mut parser = Parser::new(s);
+    let value = parser.parse()?;
+    Ok((value, parser.max_unit.unwrap_or(Unit::Nanos)))
+}
+
+/// Duration with calendar months kept apart from the fixed-length part
+///
+/// Returned by [`parse_calendar_duration`](parse_calendar_duration).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CalendarDuration {
+    /// Number of calendar months, a year is 12 months
+    pub months: i64,
+    /// Whole seconds of the fixed-length part
+    pub seconds: u64,
+    /// Nanoseconds of the fixed-length part, `0..1_000_000_000`
+    pub nanos: u32,
+}
+
+impl CalendarDuration {
+    /// Adds the duration to the timestamp, months first
+    ///
+    /// Whole months are added calendar-correctly: if the day doesn't exist
+    /// in the target month, the last day of the month is used (so Jan 31
+    /// plus one month is Feb 28 or Feb 29). Then the fixed-length part is
+    /// added. Result must be in the range supported by the timestamp
+    /// parser, otherwise `OutOfRange` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use humantime::{parse_calendar_duration, parse_rfc3339};
+    ///
+    /// let d = parse_calendar_duration("1month 2h").unwrap();
+    /// let t = parse_rfc3339("2019-01-31T10:00:00Z").unwrap();
+    /// assert_eq!(d.add_to(t),
+    ///            parse_rfc3339("2019-02-28T12:00:00Z"));
+    /// ```
+    pub fn add_to(&self, t: SystemTime) -> Result<SystemTime, DateError> {
+        let t = add_months(t, self.months)?;
+        let fixed = Duration::new(self.seconds, self.nanos);
+        t.checked_add(fixed)
+            .filter(|&t| clamp_to_range(t) == t)
+            .ok_or(DateError::OutOfRange)
+    }
+}
+
+/// Parse duration object keeping months and years as calendar months
+///
+/// Months and years have no fixed length, so summing them into a
+/// `Duration` loses information. This function accepts the same format as
+/// [`parse_duration`](parse_duration) but counts months and years (as 12
+/// months) separately, so they can be applied to a real date.
+///
+/// Fractional months that don't make up a whole month, like in `1.5M`, are
+/// added to the fixed-length part using 30.44 days per month, as
+/// `parse_duration` does.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{parse_calendar_duration, CalendarDuration};
+///
+/// assert_eq!(parse_calendar_duration("1year 2months 5days 3s"),
+///            Ok(CalendarDuration { months: 14, seconds: 432003, nanos: 0 }));
+/// ```
+pub fn parse_calendar_duration(s: &str) -> Result<CalendarDuration, Error> {
+    let mut parser = Parser::new(s);
+    parser.months = Some(0);
+    let value = parser.parse()?;
+    Ok(CalendarDuration {
+        months: parser.months.unwrap_or(0),
+        seconds: value.as_secs(),
+        nanos: value.subsec_nanos(),
+    })
+}
+
+/// Parse duration object reporting all the errors found
+///
+/// Unlike [`parse_duration`](parse_duration) this function doesn't stop at
+/// the first unknown unit or missing number, but records each of them and
+/// continues with the next time span. This is useful to show all problems
+/// to the user at once.
+///
+/// Other errors (like [`NumberOverflow`](Error::NumberOverflow)) stop
+/// parsing, and are returned as the last element of the list.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::parse_duration_verbose;
+///
+/// let errors = parse_duration_verbose("2x 5min 3y").unwrap_err();
+/// assert_eq!(errors.len(), 1);
+/// let errors = parse_duration_verbose("2x 5min 3z").unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// ```
+pub fn parse_duration_verbose(s: &str) -> Result<Duration, Vec<Error>> {
+    let mut parser = Parser::new(s);
+    parser.errors = Some(Vec::new());
+    let result = parser.parse();
+    let mut errors = parser.errors.unwrap_or_default();
+    match result {
+        Ok(value) if errors.is_empty() => Ok(value),
+        Ok(_) => Err(errors),
+        // whole input is skipped as invalid, so it's not really empty
+        Err(Error::Empty) if !errors.is_empty() => Err(errors),
+        Err(e) => {
+            errors.push(e);
+            Err(errors)
+        }
+    }
+}
+
+/// Parse duration that may be surrounded by quotes `"30s"` or `'30s'`
+///
+/// A single matching pair of quotes is stripped before parsing, which is
+/// handy for values taken verbatim from config files. A quote at only one
+/// end, or quotes that don't match, result in
+/// [`InvalidCharacter`](Error::InvalidCharacter) pointing to the quote.
+/// Error offsets refer to the original string.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_unquoted;
+///
+/// assert_eq!(parse_duration_unquoted("\"30s\""), Ok(Duration::new(30, 0)));
+/// assert_eq!(parse_duration_unquoted("'30s'"), Ok(Duration::new(30, 0)));
+/// assert_eq!(parse_duration_unquoted("30s"), Ok(Duration::new(30, 0)));
+/// ```
+pub fn parse_duration_unquoted(s: &str) -> Result<Duration, Error> {
+    let is_quote = |c: Option<char>| matches!(c, Some('"') | Some('\''));
+    let first = s.chars().next();
+    let last = s.chars().next_back();
+    match (is_quote(first), is_quote(last)) {
+        (true, true) if s.len() >= 2 && first == last => {
+            parse_duration(&s[1..s.len() - 1]).map_err(|e| e.offset_by(1))
+        }
+        (true, _) => Err(Error::InvalidCharacter(0)),
+        (false, true) => Err(Error::InvalidCharacter(s.len() - 1)),
+        (false, false) => parse_duration(s),
+    }
+}
+
+/// Parse duration relative to the base value `base+5m`
+///
+/// The string is the `base` keyword optionally followed by `+` or `-` and
+/// a duration in the format of [`parse_duration`](parse_duration).
+/// Whitespace around the sign is allowed. A string that doesn't start with
+/// `base` is parsed as an absolute duration, which is convenient for
+/// configuration overrides. Results that would be negative or don't fit
+/// into `Duration` are [`NumberOverflow`](Error::NumberOverflow).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_offset;
+///
+/// let base = Duration::new(60, 0);
+/// assert_eq!(parse_duration_offset("base+5m", base),
+///            Ok(Duration::new(360, 0)));
+/// assert_eq!(parse_duration_offset("base - 30s", base),
+///            Ok(Duration::new(30, 0)));
+/// assert_eq!(parse_duration_offset("2m", base), Ok(Duration::new(120, 0)));
+/// ```
+pub fn parse_duration_offset(s: &str, base: Duration)
+    -> Result<Duration, Error>
+{
+    let trimmed = s.trim_start();
+    let rest = match trimmed.strip_prefix("base") {
+        Some(rest) => rest,
+        None => return parse_duration(s),
+    };
+    let sign_off = s.len() - rest.trim_start().len();
+    let mut chars = rest.trim_start().chars();
+    let sign = match chars.next() {
+        None => return Ok(base),
+        Some(c @ '+') | Some(c @ '-') => c,
+        Some(_) => return Err(Error::InvalidCharacter(sign_off)),
+    };
+    let offset = parse_duration(chars.as_str())
+        .map_err(|e| e.offset_by(sign_off + 1))?;
+    let value = if sign == '+' {
+        base.checked_add(offset)
+    } else {
+        base.checked_sub(offset)
+    };
+    value.ok_or(Error::NumberOverflow)
+}
+
+/// Parse as much of the duration as possible, never failing
+///
+/// Returns the sum of the valid time spans before the first error, and
+/// that error with its byte offset in the string (or `None` if the whole
+/// string is valid). This is useful for incremental feedback in editors:
+/// show the value parsed so far and underline the error.
+///
+/// The offset is the start of the first label of the error (see
+/// [`Error::labels`](Error::labels)), or the position where parsing
+/// stopped for errors without labels.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_partial;
+///
+/// let (value, err) = parse_duration_partial("1h 30min 5x");
+/// assert_eq!(value, Duration::new(5400, 0));
+/// assert_eq!(err.map(|(_, offset)| offset), Some(10));
+/// ```
+pub fn parse_duration_partial(s: &str) -> (Duration, Option<(Error, usize)>) {
+    let mut parser = Parser::new(s);
+    match parser.parse() {
+        Ok(value) => (value, None),
+        Err(e) => {
+            let value = Duration::new(parser.current.0,
+                                      parser.current.1 as u32);
+            let offset = e.labels(s).first()
+                .map(|(range, _)| range.start)
+                .unwrap_or_else(|| parser.off());
+            (value, Some((e, offset)))
+        }
+    }
+}
+
+/// Parses a duration from every line of the reader
+///
+/// Lines are read lazily, so the whole input is never loaded into memory.
+/// The outer `Result` is an I/O error reading the line, the inner one is
+/// the result of [`parse_duration`](parse_duration) for that line.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use std::time::Duration;
+/// use humantime::parse_durations_from_read;
+///
+/// let mut iter = parse_durations_from_read(Cursor::new("5s\n2min\n"));
+/// assert_eq!(iter.next().unwrap().unwrap(), Ok(Duration::new(5, 0)));
+/// assert_eq!(iter.next().unwrap().unwrap(), Ok(Duration::new(120, 0)));
+/// assert!(iter.next().is_none());
+/// ```
+pub fn parse_durations_from_read<R: BufRead>(r: R)
+    -> impl Iterator<Item = io::Result<Result<Duration, Error>>>
+{
+    r.lines().map(|line| line.map(|line| parse_duration(&line)))
+}
+
+/// Sums durations returning an error on overflow instead of panicking
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{sum_durations, parse_duration, DurationError};
+///
+/// let items = ["1h", "30min", "15s"].iter()
+///     .map(|s| parse_duration(s).unwrap());
+/// assert_eq!(sum_durations(items), Ok(Duration::new(5415, 0)));
+/// assert_eq!(sum_durations(vec![Duration::MAX, Duration::new(0, 1)]),
+///            Err(DurationError::NumberOverflow));
+/// ```
+pub fn sum_durations<I>(iter: I) -> Result<Duration, Error>
+    where I: IntoIterator<Item = Duration>,
+{
+    iter.into_iter().try_fold(Duration::ZERO, |sum, item| {
+        sum.checked_add(item).ok_or(Error::NumberOverflow)
+    })
+}
+
+/// Returns true if the duration is shorter than the threshold
+///
+/// This is just `d < threshold`, but reads better in configuration-driven
+/// code.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{is_negligible, parse_duration};
+///
+/// let threshold = parse_duration("1ms").unwrap();
+/// assert!(is_negligible(Duration::from_micros(999), threshold));
+/// assert!(!is_negligible(Duration::from_millis(1), threshold));
+/// ```
+pub fn is_negligible(d: Duration, threshold: Duration) -> bool {
+    d < threshold
+}
+
+/// Formats duration into a human-readable string
+///
+/// Note: this format is guaranteed to have same value when using
+/// parse_duration, but we can change some details of the exact composition
+/// of the value.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::format_duration;
+///
+/// let val1 = Duration::new(9420, 0);
+/// assert_eq!(format_duration(val1).to_string(), "2h 37m");
+/// let val2 = Duration::new(0, 32_000_000);
+/// assert_eq!(format_duration(val2).to_string(), "32ms");
+/// ```
+pub fn format_duration(val: Duration) -> FormattedDuration {
+    FormattedDuration(val, Style::Short)
+}
+
+/// Formats duration for prose `2 hours, 37 minutes and 15 seconds`
+///
+/// Components use full unit names and are joined with commas, the last one
+/// with "and". Zero duration is `0 seconds`. This is
+/// [`Style::Prose`](Style::Prose).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::format_duration_prose;
+///
+/// assert_eq!(format_duration_prose(Duration::new(9435, 0)).to_string(),
+///            "2 hours, 37 minutes and 15 seconds");
+/// ```
+pub fn format_duration_prose(val: Duration) -> FormattedDuration {
+    FormattedDuration(val, Style::Prose)
+}
+
+/// Formats duration as total number of milliseconds `1500`
+///
+/// This is the format of JavaScript timestamps and timeouts. Fractions of
+/// a millisecond are truncated. Parse it back with
+/// [`parse_millis`](parse_millis).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::format_duration_total_millis;
+///
+/// assert_eq!(format_duration_total_millis(Duration::new(1, 500_000_000)),
+///            "1500");
+/// ```
+pub fn format_duration_total_millis(val: Duration) -> String {
+    val.as_millis().to_string()
+}
+
+/// Parse integer number of milliseconds `1500`
+///
+/// Only ASCII digits are allowed, no units, signs or spaces. This is the
+/// counterpart of
+/// [`format_duration_total_millis`](format_duration_total_millis).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_millis;
+///
+/// assert_eq!(parse_millis("1500"), Ok(Duration::new(1, 500_000_000)));
+/// ```
+pub fn parse_millis(s: &str) -> Result<Duration, Error> {
+    if s.is_empty() {
+        return Err(Error::Empty);
+    }
+    if let Some(off) = s.find(|c: char| !c.is_ascii_digit()) {
+        return Err(Error::InvalidCharacter(off));
+    }
+    s.parse().map(Duration::from_millis).map_err(|_| Error::NumberOverflow)
+}
+
+/// Formats duration as seconds in scientific notation `1.50e0 s`
+///
+/// `precision` is the number of mantissa digits after the decimal point.
+/// The value is converted to `f64`, so it's approximate for durations
+/// with more than about 15 significant digits. The output is accepted by
+/// [`parse_duration_scientific`](parse_duration_scientific).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::format_duration_scientific;
+///
+/// assert_eq!(format_duration_scientific(Duration::from_millis(1500), 2),
+///            "1.50e0 s");
+/// assert_eq!(format_duration_scientific(Duration::from_millis(500), 2),
+///            "5.00e-1 s");
+/// ```
+pub fn format_duration_scientific(val: Duration, precision: usize) -> String {
+    format!("{:.*e} s", precision, val.as_secs_f64())
+}
+
+/// Formats duration as days and hours `5d 3h`
+///
+/// Weeks, months and years are never used, so the output is unambiguous
+/// for things like uptime. Minutes and smaller units are truncated, so a
+/// duration shorter than an hour is `0h`. This is
+/// [`Style::DaysHours`](Style::DaysHours).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::format_duration_dh;
+///
+/// let val = Duration::new(40 * 86400 + 3 * 3600 + 25 * 60, 0);
+/// assert_eq!(format_duration_dh(val).to_string(), "40d 3h");
+/// ```
+pub fn format_duration_dh(val: Duration) -> FormattedDuration {
+    FormattedDuration(val, Style::DaysHours)
+}
+
+/// Formats duration into a string in the specified style
+///
+/// Only [`Style::Short`](Style::Short) can be parsed back with
+/// [`parse_duration`](parse_duration).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{humanize, Style};
+///
+/// let val = Duration::new(93784, 0);
+/// assert_eq!(humanize(val, Style::Short), "1day 2h 3m 4s");
+/// assert_eq!(humanize(val, Style::Long), "1 day 2 hours 3 minutes 4 seconds");
+/// assert_eq!(humanize(val, Style::Clock), "26:03:04");
+/// ```
+pub fn humanize(val: Duration, style: Style) -> String {
+    FormattedDuration(val, style).to_string()
+}
+
+/// A configurable duration formatter
+///
+/// By default it's the same as [`format_duration`](format_duration).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::DurationFormat;
+///
+/// let mut fmt = DurationFormat::new();
+/// fmt.significant(2);
+/// let val = Duration::new(9435, 500_000_000);
+/// assert_eq!(fmt.format(val).to_string(), "2h 37m");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DurationFormat {
+    style: Style,
+    significant: Option<usize>,
+}
+
+impl DurationFormat {
+    /// Creates a formatter with default settings
+    pub fn new() -> DurationFormat {
+        DurationFormat {
+            style: Style::Short,
+            significant: None,
+        }
+    }
+
+    /// Sets style of the output, [`Style::Short`](Style::Short) by default
+    pub fn style(&mut self, style: Style) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    /// Shows at most `n` units, starting from the largest non-zero one
+    ///
+    /// The rest is truncated, so `2h 37m 15s` is `2h 37m` if `n` is two,
+    /// and `2h 0m 15s` is just `2h`. This avoids misleading precision of
+    /// values like `1year 2months 3days 4h 5m 6s 7ms`. Unlimited by
+    /// default.
+    pub fn significant(&mut self, n: usize) -> &mut Self {
+        self.significant = Some(n);
+        self
+    }
+
+    /// Formats duration using configured settings
+    ///
+    /// [`get_ref`](FormattedDuration::get_ref) of the result returns the
+    /// truncated duration.
+    pub fn format(&self, val: Duration) -> FormattedDuration {
+        let val = match self.significant {
+            Some(n) => truncate_units(val, n),
+            None => val,
+        };
+        FormattedDuration(val, self.style)
+    }
+}
+
+impl Default for DurationFormat {
+    fn default() -> DurationFormat {
+        DurationFormat::new()
+    }
+}
+
+/// Duration split into calendar-free components
+///
+/// See [`breakdown`](breakdown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DurationParts {
+    /// Whole days
+    pub days: u64,
+    /// Hours, `0..24`
+    pub hours: u8,
+    /// Minutes, `0..60`
+    pub minutes: u8,
+    /// Seconds, `0..60`
+    pub seconds: u8,
+    /// Nanoseconds, `0..1_000_000_000`
+    pub nanos: u32,
+}
+
+/// Splits duration into days, hours, minutes, seconds and nanoseconds
+///
+/// Weeks, months and years are intentionally not used, so all the days
+/// are in the `days` field.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::breakdown;
+///
+/// let parts = breakdown(Duration::new(90061, 5));
+/// assert_eq!((parts.days, parts.hours, parts.minutes, parts.seconds),
+///            (1, 1, 1, 1));
+/// assert_eq!(parts.nanos, 5);
+/// ```
+pub fn breakdown(val: Duration) -> DurationParts {
+    let secs = val.as_secs();
+    DurationParts {
+        days: secs / 86400,
+        hours: (secs % 86400 / 3600) as u8,
+        minutes: (secs % 3600 / 60) as u8,
+        seconds: (secs % 60) as u8,
+        nanos: val.subsec_nanos(),
+    }
+}
+
+/// Formats duration into a human-readable string `2h 37m`
+///
+/// This is a shortcut for `format_duration(val).to_string()`.
+pub fn duration_string(val: Duration) -> String {
+    format_duration(val).to_string()
+}
+
+/// Truncates duration to at most `count` units as `format_duration` shows
+///
+/// Units are counted from the largest non-zero one, so `1h 2m 8s` is
+/// `1h 2m` and `1h 0m 8s` is `1h` if count is two.
+pub(crate) fn truncate_units(val: Duration, count: usize) -> Duration {
+    const UNITS: [u128; 9] = [
+        31_557_600_000_000_000,  // 365.25d
+        2_630_016_000_000_000,  // 30.44d
+        86_400_000_000_000,
+        3_600_000_000_000,
+        60_000_000_000,
+        1_000_000_000,
+        1_000_000,
+        1_000,
+        1,
+    ];
+    let nanos = val.as_nanos();
+    let mut rem = nanos;
+    let mut taken = 0;
+    for &unit in UNITS.iter() {
+        if taken >= count {
+            break;
+        }
+        if taken > 0 || rem >= unit {
+            taken += 1;
+        }
+        rem %= unit;
+    }
+    let keep = nanos - rem;
+    Duration::new((keep / 1_000_000_000) as u64,
+                  (keep % 1_000_000_000) as u32)
+}
+
+fn item_plural(f: &mut fmt::Formatter, started: &mut bool,
+    name: &str, value: u64, long: bool)
+    -> fmt::Result
+{
+    if value > 0 {
+        if *started {
+            f.write_str(" ")?;
+        }
+        write!(f, "{}{}{}", value, if long { " " } else { "" }, name)?;
+        if value > 1 {
+            f.write_str("s")?;
+        }
+        *started = true;
+    }
+    Ok(())
+}
+
+/// Writes nanoseconds as a fraction of a second `.5`
+///
+/// Trailing zeros are trimmed, nothing is written for zero.
+#[allow(clippy::manual_is_multiple_of)]
+pub(crate) fn write_nanos_fraction<W: fmt::Write>(w: &mut W, nanos: u32)
+    -> fmt::Result
+{
+    if nanos == 0 {
+        return Ok(());
+    }
+    let mut value = nanos;
+    let mut digits = 9;
+    while value % 10 == 0 {
+        value /= 10;
+        digits -= 1;
+    }
+    write!(w, ".{:0width$}", value, width = digits)
+}
+
+const LONG_NAMES: [&str; 9] = [
+    "year", "month", "day", "hour", "minute", "second",
+    "millisecond", "microsecond", "nanosecond",
+];
+
+fn item(f: &mut fmt::Formatter, started: &mut bool, name: &str, value: u64)
+    -> fmt::Result
+{
+    if value > 0 {
+        if *started {
+            f.write_str(" ")?;
         }
+        write!(f, "{}{}", value, name)?;
+        *started = true;
+    }
+    Ok(())
+}
+
+impl FormattedDuration {
+    /// Returns a reference to the [`Duration`][] that is being formatted.
+    pub fn get_ref(&self) -> &Duration {
+        &self.0
+    }
+
+    /// Returns formatted duration as a JSON string literal, i.e. in quotes
+    ///
+    /// Formatted duration consists only of ASCII letters, digits, spaces,
+    /// colons, dots and commas, so no escaping is needed.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use humantime::format_duration;
+    ///
+    /// let val = Duration::new(9420, 0);
+    /// assert_eq!(format_duration(val).to_json_string(), r#""2h 37m""#);
+    /// ```
+    pub fn to_json_string(&self) -> String {
+        format!("\"{}\"", self)
+    }
+
+    /// Splits duration into years, months, days, hours, minutes, seconds,
+    /// milliseconds, microseconds and nanoseconds
+    fn components(&self) -> [u64; 9] {
+        let secs = self.0.as_secs();
+        let nanos = self.0.subsec_nanos() as u64;
 
         let years = secs / 31_557_600;  // 365.25d
         let ydays = secs % 31_557_600;
@@ -312,31 +2163,120 @@ impl fmt::Display for FormattedDuration {
         let minutes = day_secs % 3600 / 60;
         let seconds = day_secs % 60;
 
+        if nanos == 0 {
+            // fast path for the common case of whole seconds
+            return [years, months, days, hours, minutes, seconds, 0, 0, 0];
+        }
         let millis = nanos / 1_000_000;
         let micros = nanos / 1000 % 1000;
         let nanosec = nanos % 1000;
+        [years, months, days, hours, minutes, seconds, millis, micros, nanosec]
+    }
+
+    fn fmt_units(&self, f: &mut fmt::Formatter, long: bool) -> fmt::Result {
+        if self.0 == Duration::ZERO {
+            f.write_str(if long { "0 seconds" } else { "0s" })?;
+            return Ok(());
+        }
+
+        let components = self.components();
+        let started = &mut false;
+        if long {
+            for (&name, &value) in LONG_NAMES.iter().zip(&components) {
+                item_plural(f, started, name, value, true)?;
+            }
+        } else {
+            let [years, months, days, hours, minutes, seconds,
+                 millis, micros, nanosec] = components;
+            item_plural(f, started, "year", years, false)?;
+            item_plural(f, started, "month", months, false)?;
+            item_plural(f, started, "day", days, false)?;
+            item(f, started, "h", hours)?;
+            item(f, started, "m", minutes)?;
+            item(f, started, "s", seconds)?;
+            item(f, started, "ms", millis)?;
+            item(f, started, "us", micros)?;
+            item(f, started, "ns", nanosec)?;
+        }
+        Ok(())
+    }
+
+    fn fmt_prose(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == Duration::ZERO {
+            return f.write_str("0 seconds");
+        }
+        let components = self.components();
+        let mut items = LONG_NAMES.iter().zip(&components)
+            .filter(|&(_, &value)| value > 0)
+            .peekable();
+        let started = &mut false;
+        while let Some((&name, &value)) = items.next() {
+            if *started {
+                f.write_str(if items.peek().is_some() { "," } else { " and" })?;
+            }
+            item_plural(f, started, name, value, true)?;
+        }
+        Ok(())
+    }
 
+    fn fmt_days_hours(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.0.as_secs();
+        if secs < 3600 {
+            return f.write_str("0h");
+        }
         let started = &mut false;
-        item_plural(f, started, "year", years)?;
-        item_plural(f, started, "month", months)?;
-        item_plural(f, started, "day", days)?;
-        item(f, started, "h", hours as u32)?;
-        item(f, started, "m", minutes as u32)?;
-        item(f, started, "s", seconds as u32)?;
-        item(f, started, "ms", millis)?;
-        item(f, started, "us", micros)?;
-        item(f, started, "ns", nanosec)?;
+        item(f, started, "d", secs / 86400)?;
+        item(f, started, "h", secs % 86400 / 3600)?;
         Ok(())
     }
+
+    fn fmt_clock(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = self.0.as_secs();
+        write!(f, "{:02}:{:02}:{:02}",
+               secs / 3600, secs % 3600 / 60, secs % 60)?;
+        write_nanos_fraction(f, self.0.subsec_nanos())
+    }
+}
+
+impl fmt::Display for FormattedDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.1 {
+            Style::Short => self.fmt_units(f, false),
+            Style::Long => self.fmt_units(f, true),
+            Style::Clock => self.fmt_clock(f),
+            Style::Prose => self.fmt_prose(f),
+            Style::DaysHours => self.fmt_days_hours(f),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::io::Cursor;
     use std::time::Duration;
 
     use rand::Rng;
 
-    use super::{parse_duration, format_duration};
+    use super::{parse_duration, parse_duration_verbose, format_duration};
+    use super::{DurationParser, Rounding, parse_duration_map};
+    use super::{parse_duration_grouped, duration_string};
+    use super::{breakdown, DurationParts, parse_duration_after_sep};
+    use super::{parse_duration_with_meta, parse_duration_scientific};
+    use super::{parse_calendar_duration, CalendarDuration};
+    use super::{parse_duration_canonical, parse_duration_unit_first};
+    use super::{humanize, Style, sum_durations, parse_duration_prefix};
+    use super::{format_duration_prose, parse_duration_schedulable};
+    use super::{format_duration_dh, parse_duration_with_default};
+    use super::{is_negligible, parse_durations_from_read};
+    use super::{parse_duration_whole_seconds, parse_millis};
+    use super::{format_duration_total_millis, parse_duration_partial};
+    use super::{parse_duration_unquoted, format_duration_scientific};
+    use super::{DurationFormat, parse_duration_offset};
+    use super::{ErrorKind, parse_duration_counts, parse_duration_percent};
+    use super::{parse_duration_u128_nanos, parse_duration_multiple_of};
+    use crate::unit::UnitTable;
+    use crate::date::{parse_rfc3339, Error as DateError};
+    use crate::unit::Unit;
     use super::Error;
 
     #[test]
@@ -350,6 +2290,16 @@ mod test {
         assert_eq!(parse_duration("31msec"), Ok(Duration::new(0, 31_000_000)));
         assert_eq!(parse_duration("31millis"), Ok(Duration::new(0, 31_000_000)));
         assert_eq!(parse_duration("6ms"), Ok(Duration::new(0, 6_000_000)));
+        assert_eq!(parse_duration("5 nanoseconds"), parse_duration("5ns"));
+        assert_eq!(parse_duration("1nanosecond"), Ok(Duration::new(0, 1)));
+        assert_eq!(parse_duration("3 microseconds"), parse_duration("3us"));
+        assert_eq!(parse_duration("1microsecond"), Ok(Duration::new(0, 1000)));
+        assert_eq!(parse_duration("2 milliseconds"), parse_duration("2ms"));
+        assert_eq!(parse_duration("1millisecond"),
+            Ok(Duration::new(0, 1_000_000)));
+        assert_eq!(parse_duration("5cs"), Ok(Duration::new(0, 50_000_000)));
+        assert_eq!(parse_duration("3ds"), Ok(Duration::new(0, 300_000_000)));
+        assert_eq!(parse_duration("15ds"), Ok(Duration::new(1, 500_000_000)));
         assert_eq!(parse_duration("3000s"), Ok(Duration::new(3000, 0)));
         assert_eq!(parse_duration("300sec"), Ok(Duration::new(300, 0)));
         assert_eq!(parse_duration("300secs"), Ok(Duration::new(300, 0)));
@@ -368,6 +2318,7 @@ mod test {
         assert_eq!(parse_duration("1day"), Ok(Duration::new(86400, 0)));
         assert_eq!(parse_duration("2days"), Ok(Duration::new(172_800, 0)));
         assert_eq!(parse_duration("365d"), Ok(Duration::new(31_536_000, 0)));
+        assert_eq!(parse_duration("3d"), Ok(Duration::new(259_200, 0)));
         assert_eq!(parse_duration("1week"), Ok(Duration::new(604_800, 0)));
         assert_eq!(parse_duration("7weeks"), Ok(Duration::new(4_233_600, 0)));
         assert_eq!(parse_duration("52w"), Ok(Duration::new(31_449_600, 0)));
@@ -385,6 +2336,963 @@ mod test {
         assert_eq!(parse_duration("2h 15m"), Ok(Duration::new(8100, 0)));
     }
 
+    #[test]
+    fn bare_zero() {
+        assert_eq!(parse_duration("0"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse_duration(" 0 "), Ok(Duration::new(0, 0)));
+        assert_eq!(parse_duration("0s"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse_duration("5"), Err(Error::UnknownUnit {
+            start: 1, end: 1, unit: "".to_string(), value: 5 }));
+        assert_eq!(parse_duration("1s 0"), Err(Error::UnknownUnit {
+            start: 4, end: 4, unit: "".to_string(), value: 0 }));
+    }
+
+    #[test]
+    fn verbose() {
+        assert_eq!(parse_duration_verbose("2h 30min"),
+            Ok(Duration::new(9000, 0)));
+        assert_eq!(parse_duration_verbose("2x 30min 3z"), Err(vec![
+            Error::UnknownUnit {
+                start: 1, end: 2, unit: "x".to_string(), value: 2 },
+            Error::UnknownUnit {
+                start: 10, end: 11, unit: "z".to_string(), value: 3 },
+        ]));
+        assert_eq!(parse_duration_verbose("2h min 3z"), Err(vec![
+            Error::NumberExpected(3),
+            Error::UnknownUnit {
+                start: 8, end: 9, unit: "z".to_string(), value: 3 },
+        ]));
+        assert_eq!(parse_duration_verbose("2x 100000000000000000000s"),
+            Err(vec![
+                Error::UnknownUnit {
+                    start: 1, end: 2, unit: "x".to_string(), value: 2 },
+                Error::NumberOverflow,
+            ]));
+        assert_eq!(parse_duration_verbose(""), Err(vec![Error::Empty]));
+        assert_eq!(parse_duration_verbose("min"),
+            Err(vec![Error::NumberExpected(0)]));
+    }
+
+    #[test]
+    fn fractional() {
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::new(1, 500_000_000)));
+        assert_eq!(parse_duration("0.25ms"), Ok(Duration::new(0, 250_000)));
+        assert_eq!(parse_duration("1.5h 0.5s"), Ok(Duration::new(5400, 500_000_000)));
+        assert_eq!(parse_duration("1.s"), Err(Error::NumberExpected(2)));
+        assert_eq!(parse_duration("1h 10.min"), Err(Error::NumberExpected(6)));
+        assert_eq!(parse_duration("1."), Err(Error::NumberExpected(2)));
+        assert_eq!(parse_duration("1.5ns"), Ok(Duration::new(0, 1)));
+        assert_eq!(parse_duration("1.2.3s"), Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse_duration("1.5d"), Ok(Duration::new(36 * 3600, 0)));
+        assert_eq!(parse_duration("0.5w"), Ok(Duration::new(302_400, 0)));
+        assert_eq!(parse_duration("0.5y"), Ok(Duration::new(15_778_800, 0)));
+        assert_eq!(parse_duration("0.5M"), Ok(Duration::new(1_315_008, 0)));
+        assert_eq!(parse_duration("0.5y"), parse_duration("182.625d"));
+        assert_eq!(parse_duration("1.5months"), parse_duration("45.66days"));
+        assert_eq!(parse_duration("0.1d"), Ok(Duration::new(8640, 0)));
+        assert_eq!(parse_duration(".5s"), Err(Error::NumberExpected(0)));
+    }
+
+    #[test]
+    fn fractional_spans() {
+        assert_eq!(parse_duration("1h 30.5m"), Ok(Duration::new(5430, 0)));
+        assert_eq!(parse_duration("1h30.5m"), Ok(Duration::new(5430, 0)));
+        assert_eq!(parse_duration("1.5h 30.5m 0.25s"),
+                   Ok(Duration::new(7230, 250_000_000)));
+        assert_eq!(parse_duration("0.5s 0.5s"), Ok(Duration::new(1, 0)));
+        assert_eq!(parse_duration("0.7s 0.7s 0.7s"),
+                   Ok(Duration::new(2, 100_000_000)));
+        assert_eq!(parse_duration("1.5h 30.5m"),
+                   Ok(parse_duration("1.5h").unwrap() +
+                      parse_duration("30.5m").unwrap()));
+    }
+
+    #[test]
+    fn rounding() {
+        let parse = |rounding, s| {
+            DurationParser::new().rounding(rounding).parse(s)
+        };
+        assert_eq!(parse(Rounding::Truncate, "1.0000000005s"),
+            Ok(Duration::new(1, 0)));
+        assert_eq!(parse(Rounding::Nearest, "1.0000000005s"),
+            Ok(Duration::new(1, 1)));
+        assert_eq!(parse(Rounding::Up, "1.0000000005s"),
+            Ok(Duration::new(1, 1)));
+        assert_eq!(parse(Rounding::Truncate, "1.0000000004s"),
+            Ok(Duration::new(1, 0)));
+        assert_eq!(parse(Rounding::Nearest, "1.0000000004s"),
+            Ok(Duration::new(1, 0)));
+        assert_eq!(parse(Rounding::Up, "1.0000000004s"),
+            Ok(Duration::new(1, 1)));
+        assert_eq!(parse(Rounding::Up, "1.0000000000s"),
+            Ok(Duration::new(1, 0)));
+        assert_eq!(DurationParser::new().parse("2h 37min"),
+            parse_duration("2h 37min"));
+    }
+
+    #[test]
+    fn map() {
+        assert_eq!(parse_duration_map("connect=5s,read=30s"), Ok(vec![
+            ("connect".to_string(), Duration::new(5, 0)),
+            ("read".to_string(), Duration::new(30, 0)),
+        ]));
+        assert_eq!(parse_duration_map(" total = 1h 30min "), Ok(vec![
+            ("total".to_string(), Duration::new(5400, 0)),
+        ]));
+        assert_eq!(parse_duration_map(""), Ok(vec![]));
+        assert_eq!(parse_duration_map("connect=5s,read"),
+            Err(Error::KeyValueExpected(11)));
+        assert_eq!(parse_duration_map("connect=5s,=30s"),
+            Err(Error::KeyValueExpected(11)));
+        assert_eq!(parse_duration_map("connect=5s,"),
+            Err(Error::KeyValueExpected(11)));
+        assert_eq!(parse_duration_map("connect=5s,read=30x"),
+            Err(Error::UnknownUnit {
+                start: 18, end: 19, unit: "x".to_string(), value: 30 }));
+        assert_eq!(parse_duration_map("connect=,read=30s"), Err(Error::Empty));
+    }
+
+    #[test]
+    fn grouped() {
+        assert_eq!(parse_duration_grouped("1.000s", '.'),
+            Ok(Duration::new(1000, 0)));
+        assert_eq!(parse_duration_grouped("1,000,000us 2,000ms", ','),
+            Ok(Duration::new(3, 0)));
+        assert_eq!(parse_duration_grouped("999s", ','),
+            Ok(Duration::new(999, 0)));
+        assert_eq!(parse_duration_grouped("10000s", ','),
+            Ok(Duration::new(10000, 0)));
+        assert_eq!(parse_duration_grouped("1.00s", '.'),
+            Err(Error::InvalidCharacter(1)));
+        assert_eq!(parse_duration_grouped("1.0000s", '.'),
+            Err(Error::InvalidCharacter(1)));
+        assert_eq!(parse_duration_grouped("1000.000s", '.'),
+            Err(Error::InvalidCharacter(4)));
+        assert_eq!(parse_duration_grouped("1..000s", '.'),
+            Err(Error::InvalidCharacter(2)));
+        assert_eq!(parse_duration_grouped("s.000", '.'),
+            Err(Error::InvalidCharacter(1)));
+        assert_eq!(parse_duration_grouped("1,000", ','),
+            Err(Error::UnknownUnit {
+                start: 5, end: 5, unit: "".to_string(), value: 1000 }));
+        assert_eq!(parse_duration_grouped("1,000x", ','),
+            Err(Error::UnknownUnit {
+                start: 5, end: 6, unit: "x".to_string(), value: 1000 }));
+    }
+
+    #[test]
+    fn to_string() {
+        for &(secs, nanos) in &[(0, 0), (9420, 0), (0, 32_000_000),
+                                (90061, 5)] {
+            let d = Duration::new(secs, nanos);
+            assert_eq!(duration_string(d), format_duration(d).to_string());
+        }
+    }
+
+    #[test]
+    fn parts() {
+        assert_eq!(breakdown(Duration::new(0, 0)), DurationParts::default());
+        assert_eq!(breakdown(Duration::new(10 * 86400 + 23 * 3600 + 59 * 60
+                                           + 58, 999_999_999)),
+            DurationParts {
+                days: 10,
+                hours: 23,
+                minutes: 59,
+                seconds: 58,
+                nanos: 999_999_999,
+            });
+        let parts = breakdown(parse_duration("1year 2h 3min 4s 5ms").unwrap());
+        assert_eq!(parts.days, 365);
+        assert_eq!(parts.hours, 8);
+        assert_eq!(parts.minutes, 3);
+        assert_eq!(parts.seconds, 4);
+        assert_eq!(parts.nanos, 5_000_000);
+    }
+
+    #[test]
+    fn after_sep() {
+        assert_eq!(parse_duration_after_sep("=5s"), Ok(Duration::new(5, 0)));
+        assert_eq!(parse_duration_after_sep(":30m"),
+            Ok(Duration::new(1800, 0)));
+        assert_eq!(parse_duration_after_sep(" = 5s"), Ok(Duration::new(5, 0)));
+        assert_eq!(parse_duration_after_sep("5s"), Ok(Duration::new(5, 0)));
+        assert_eq!(parse_duration_after_sep("5s=3m"),
+            Err(Error::InvalidCharacter(2)));
+        assert_eq!(parse_duration_after_sep("==5s"),
+            Err(Error::NumberExpected(1)));
+        assert_eq!(parse_duration_after_sep("=5x"),
+            Err(Error::UnknownUnit {
+                start: 2, end: 3, unit: "x".to_string(), value: 5 }));
+        assert_eq!(parse_duration_after_sep("="), Err(Error::Empty));
+    }
+
+    #[test]
+    fn with_meta() {
+        assert_eq!(parse_duration_with_meta("2h 30min"),
+            Ok((Duration::new(9000, 0), Unit::Hours)));
+        assert_eq!(parse_duration_with_meta("500ms"),
+            Ok((Duration::new(0, 500_000_000), Unit::Millis)));
+        assert_eq!(parse_duration_with_meta("5s 1M 3d"),
+            Ok((Duration::new(2_630_016 + 3 * 86400 + 5, 0), Unit::Months)));
+        assert_eq!(parse_duration_with_meta("0"),
+            Ok((Duration::new(0, 0), Unit::Nanos)));
+        assert_eq!(parse_duration_with_meta("5x"),
+            Err(Error::UnknownUnit {
+                start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+    }
+
+    #[test]
+    fn unexpected_sign() {
+        assert_eq!(parse_duration("5min -3s"), Err(Error::UnexpectedSign(5)));
+        assert_eq!(parse_duration("5min +3s"), Err(Error::UnexpectedSign(5)));
+        assert_eq!(parse_duration("5min  -3s"), Err(Error::UnexpectedSign(6)));
+        assert_eq!(parse_duration("5min-3s"), Err(Error::UnexpectedSign(4)));
+        assert_eq!(parse_duration("-3s"), Err(Error::NumberExpected(0)));
+        assert_eq!(parse_duration("5min -3s").unwrap_err().to_string(),
+            "unexpected sign at 5, time spans can't be subtracted");
+        assert_eq!(parse_duration_verbose("5min -3s 2x"), Err(vec![
+            Error::UnexpectedSign(5),
+            Error::UnknownUnit {
+                start: 10, end: 11, unit: "x".to_string(), value: 2 },
+        ]));
+        assert_eq!(parse_duration_verbose("5min-3s 7x"), Err(vec![
+            Error::UnexpectedSign(4),
+            Error::UnknownUnit {
+                start: 9, end: 10, unit: "x".to_string(), value: 7 },
+        ]));
+        assert_eq!(parse_duration_verbose("5x-3s"), Err(vec![
+            Error::UnexpectedSign(2),
+            Error::UnknownUnit {
+                start: 1, end: 2, unit: "x".to_string(), value: 5 },
+        ]));
+        assert_eq!(parse_duration_verbose("5min-"),
+                   Err(vec![Error::UnexpectedSign(4)]));
+    }
+
+    #[test]
+    fn labels() {
+        let src = "2h 10nights";
+        let labels = parse_duration(src).unwrap_err().labels(src);
+        assert_eq!(labels, vec![(5..11, "unknown time unit".to_string())]);
+        assert_eq!(&src[labels[0].0.clone()], "nights");
+
+        let src = "2h 10";
+        let labels = parse_duration(src).unwrap_err().labels(src);
+        assert_eq!(labels, vec![
+            (5..5, "time unit needed, for example 10sec".to_string())]);
+
+        let src = "2h\u{2603}";
+        let labels = parse_duration(src).unwrap_err().labels(src);
+        assert_eq!(labels, vec![(2..5, "invalid character".to_string())]);
+
+        let src = "a=1s,b,c=2s";
+        let labels = parse_duration_map(src).unwrap_err().labels(src);
+        assert_eq!(labels, vec![
+            (5..6, "expected `key=duration`".to_string())]);
+
+        assert_eq!(Error::NumberOverflow.labels("1000000000000000000000s"),
+            vec![]);
+    }
+
+    #[test]
+    fn scientific() {
+        let parse = parse_duration_scientific;
+        assert_eq!(parse("1e3ms"), Ok(Duration::new(1, 0)));
+        assert_eq!(parse("1E3ms"), Ok(Duration::new(1, 0)));
+        assert_eq!(parse("1e+3ms"), Ok(Duration::new(1, 0)));
+        assert_eq!(parse("15e-1s"), Ok(Duration::new(1, 500_000_000)));
+        assert_eq!(parse("1.5e1s"), Ok(Duration::new(15, 0)));
+        assert_eq!(parse("1.25e1s"), Ok(Duration::new(12, 500_000_000)));
+        assert_eq!(parse("1e-9s"), Ok(Duration::new(0, 1)));
+        assert_eq!(parse("1e-100s"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse("0e100s"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse("2e1h 1e1min"), Ok(Duration::new(72600, 0)));
+        assert_eq!(parse("1e3"), Err(Error::UnknownUnit {
+            start: 3, end: 3, unit: "".to_string(), value: 1000 }));
+        assert_eq!(parse("1e20s"), Err(Error::NumberOverflow));
+        assert_eq!(parse("1e99999999999s"), Err(Error::NumberOverflow));
+        assert_eq!(parse("1e3.5s"), Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse("1e3 5s"), Err(Error::InvalidCharacter(4)));
+        // not an exponent, so it's a unit
+        assert_eq!(parse("1ex"), Err(Error::UnknownUnit {
+            start: 1, end: 3, unit: "ex".to_string(), value: 1 }));
+        assert_eq!(parse("1e-s"), Err(Error::UnexpectedSign(2)));
+        assert_eq!(parse_duration("1e3ms"), Err(Error::UnknownUnit {
+            start: 1, end: 2, unit: "e".to_string(), value: 1 }));
+    }
+
+    #[test]
+    fn calendar() {
+        let cal = |months, seconds, nanos| {
+            Ok(CalendarDuration { months, seconds, nanos })
+        };
+        assert_eq!(parse_calendar_duration("1month 5days"), cal(1, 432000, 0));
+        assert_eq!(parse_calendar_duration("2y 1M"), cal(25, 0, 0));
+        assert_eq!(parse_calendar_duration("1.5y"), cal(18, 0, 0));
+        assert_eq!(parse_calendar_duration("1.5M"), cal(1, 1_315_008, 0));
+        assert_eq!(parse_calendar_duration("0.25y"), cal(3, 0, 0));
+        assert_eq!(parse_calendar_duration("5min 30ms"),
+                   cal(0, 300, 30_000_000));
+        assert_eq!(parse_calendar_duration("0"), cal(0, 0, 0));
+        assert_eq!(parse_calendar_duration("9223372036854775808M"),
+                   Err(Error::NumberOverflow));
+        assert_eq!(parse_calendar_duration("1month 5nights"),
+                   Err(Error::UnknownUnit {
+                       start: 8, end: 14,
+                       unit: "nights".to_string(), value: 5 }));
+        // fixed-length parsing is not affected
+        assert_eq!(parse_duration("1month 5days"),
+                   Ok(Duration::new(2630016 + 432000, 0)));
+    }
+
+    #[test]
+    fn calendar_add_to() {
+        let add = |d: &str, t: &str| {
+            parse_calendar_duration(d).unwrap()
+                .add_to(parse_rfc3339(t).unwrap())
+        };
+        let ts = |t: &str| Ok(parse_rfc3339(t).unwrap());
+        assert_eq!(add("1month", "2019-01-31T10:00:00Z"),
+                   ts("2019-02-28T10:00:00Z"));
+        assert_eq!(add("1month", "2020-01-31T10:00:00Z"),
+                   ts("2020-02-29T10:00:00Z"));
+        assert_eq!(add("1year", "2020-02-29T00:00:00Z"),
+                   ts("2021-02-28T00:00:00Z"));
+        assert_eq!(add("4years", "2020-02-29T00:00:00Z"),
+                   ts("2024-02-29T00:00:00Z"));
+        assert_eq!(add("1month 1day", "2019-01-31T23:30:00.5Z"),
+                   ts("2019-03-01T23:30:00.5Z"));
+        assert_eq!(add("11months", "2019-02-15T00:00:00Z"),
+                   ts("2020-01-15T00:00:00Z"));
+        assert_eq!(add("1h", "2019-02-15T00:00:00Z"),
+                   ts("2019-02-15T01:00:00Z"));
+        let back = CalendarDuration { months: -1, seconds: 0, nanos: 0 };
+        assert_eq!(back.add_to(parse_rfc3339("2019-03-31T00:00:00Z").unwrap()),
+                   ts("2019-02-28T00:00:00Z"));
+        assert_eq!(back.add_to(parse_rfc3339("1970-01-15T00:00:00Z").unwrap()),
+                   Err(DateError::OutOfRange));
+    }
+
+    #[test]
+    fn canonical() {
+        let parse = parse_duration_canonical;
+        assert_eq!(parse("2h 37min"), Ok(Duration::new(9420, 0)));
+        assert_eq!(parse("2h37min"), Ok(Duration::new(9420, 0)));
+        assert_eq!(parse("0"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse("2h  37min"), Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse("2 h"), Err(Error::InvalidCharacter(1)));
+        assert_eq!(parse(" 2h"), Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse("2h "), Err(Error::InvalidCharacter(2)));
+        assert_eq!(parse("2h  "), Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse("2h\t37min"), Err(Error::InvalidCharacter(2)));
+        assert_eq!(parse("1 2s"), Err(Error::InvalidCharacter(1)));
+        assert_eq!(parse("2h 37nights"), Err(Error::UnknownUnit {
+            start: 5, end: 11, unit: "nights".to_string(), value: 37 }));
+    }
+
+    #[test]
+    fn unit_first() {
+        let parse = parse_duration_unit_first;
+        assert_eq!(parse("min 5 sec 30"), Ok(Duration::new(330, 0)));
+        assert_eq!(parse("min5 sec30"), Ok(Duration::new(330, 0)));
+        assert_eq!(parse("  h 1.5  "), Ok(Duration::new(5400, 0)));
+        assert_eq!(parse("ms 0"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse(""), Err(Error::Empty));
+        assert_eq!(parse("min 5 30s"), Err(Error::InvalidCharacter(6)));
+        assert_eq!(parse("5min sec 30"), Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse("min 5sec"), Err(Error::InvalidCharacter(5)));
+        assert_eq!(parse("min sec 30"), Err(Error::NumberExpected(4)));
+        assert_eq!(parse("min"), Err(Error::NumberExpected(3)));
+        assert_eq!(parse("nights 5"), Err(Error::UnknownUnit {
+            start: 0, end: 6, unit: "nights".to_string(), value: 5 }));
+        assert_eq!(parse("s 18446744073709551616"),
+                   Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn humanize_styles() {
+        let val = Duration::new(2 * 86400 + 3600 + 62, 5_000_000);
+        assert_eq!(humanize(val, Style::Short), "2days 1h 1m 2s 5ms");
+        assert_eq!(humanize(val, Style::Long),
+                   "2 days 1 hour 1 minute 2 seconds 5 milliseconds");
+        assert_eq!(humanize(val, Style::Clock), "49:01:02.005");
+        let zero = Duration::new(0, 0);
+        assert_eq!(humanize(zero, Style::Short), "0s");
+        assert_eq!(humanize(zero, Style::Long), "0 seconds");
+        assert_eq!(humanize(zero, Style::Clock), "00:00:00");
+        let small = Duration::new(0, 1_002_003);
+        assert_eq!(humanize(small, Style::Long),
+                   "1 millisecond 2 microseconds 3 nanoseconds");
+        assert_eq!(humanize(small, Style::Clock), "00:00:00.001002003");
+        assert_eq!(humanize(Duration::new(31_557_600, 0), Style::Long),
+                   "1 year");
+        assert_eq!(humanize(val, Style::Short),
+                   format_duration(val).to_string());
+    }
+
+    #[test]
+    fn ordinals() {
+        let mut parser = DurationParser::new();
+        parser.ordinals(true);
+        assert_eq!(parser.parse("5th day"), Ok(Duration::new(432_000, 0)));
+        assert_eq!(parser.parse("1st hour 2nd min 3rd  s"),
+                   Ok(Duration::new(3723, 0)));
+        assert_eq!(parser.parse("5th"), Err(Error::UnknownUnit {
+            start: 3, end: 3, unit: "".to_string(), value: 5 }));
+        assert_eq!(parser.parse("5 th day"), Err(Error::UnknownUnit {
+            start: 2, end: 4, unit: "th".to_string(), value: 5 }));
+        assert_eq!(parser.parse("5th th"), Err(Error::UnknownUnit {
+            start: 4, end: 6, unit: "th".to_string(), value: 5 }));
+        assert_eq!(parser.parse("5.5th day"), Err(Error::UnknownUnit {
+            start: 3, end: 5, unit: "th".to_string(), value: 5 }));
+        // non-ordinal inputs are unaffected
+        assert_eq!(parser.parse("1h 30min"), Ok(Duration::new(5400, 0)));
+        assert_eq!(parser.parse("5 days 2h"), Ok(Duration::new(439_200, 0)));
+        assert_eq!(parser.parse("5x"), Err(Error::UnknownUnit {
+            start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+        assert_eq!(DurationParser::new().parse("5th day"),
+                   Err(Error::UnknownUnit {
+                       start: 1, end: 3, unit: "th".to_string(), value: 5 }));
+        let mut table = UnitTable::new();
+        table.add("th", Unit::Hours);
+        parser.units(&table);
+        assert_eq!(parser.parse("5th"), Ok(Duration::new(18000, 0)));
+    }
+
+    #[test]
+    fn implicit_one() {
+        let mut parser = DurationParser::new();
+        parser.implicit_one(true);
+        assert_eq!(parser.parse("h"), Ok(Duration::new(3600, 0)));
+        assert_eq!(parser.parse("m s"), Ok(Duration::new(61, 0)));
+        assert_eq!(parser.parse("  day 2h"), Ok(Duration::new(93600, 0)));
+        assert_eq!(parser.parse("5min ms"),
+                   Ok(Duration::new(300, 1_000_000)));
+        assert_eq!(parser.parse("3"), Err(Error::UnknownUnit {
+            start: 1, end: 1, unit: "".to_string(), value: 3 }));
+        assert_eq!(parser.parse("nights"), Err(Error::UnknownUnit {
+            start: 0, end: 6, unit: "nights".to_string(), value: 1 }));
+        assert_eq!(parser.parse("h $"), Err(Error::NumberExpected(2)));
+        assert_eq!(parser.parse(""), Err(Error::Empty));
+        assert_eq!(DurationParser::new().parse("h"),
+                   Err(Error::NumberExpected(0)));
+        assert_eq!(parse_duration("m s"), Err(Error::NumberExpected(0)));
+    }
+
+    #[test]
+    fn custom_units() {
+        let mut table = UnitTable::new();
+        table
+            .add("Stunde", Unit::Hours).add("Stunden", Unit::Hours)
+            .add("Minute", Unit::Minutes).add("Minuten", Unit::Minutes)
+            .add("Sekunde", Unit::Seconds).add("Sekunden", Unit::Seconds)
+            .add("m", Unit::Months);
+        let mut parser = DurationParser::new();
+        parser.units(&table);
+        assert_eq!(parser.parse("2 Stunden 30 Minuten"),
+                   Ok(Duration::new(9000, 0)));
+        assert_eq!(parser.parse("1 Stunde 1 Sekunde"),
+                   Ok(Duration::new(3601, 0)));
+        assert_eq!(parser.parse("1 Stunde 30min"),
+                   Ok(Duration::new(5400, 0)));
+        // table takes precedence
+        assert_eq!(parser.parse("1m"), Ok(Duration::new(2_630_016, 0)));
+        assert_eq!(parser.parse("2 Tage"), Err(Error::UnknownUnit {
+            start: 2, end: 6, unit: "Tage".to_string(), value: 2 }));
+        assert_eq!(DurationParser::new().parse("2 Stunden"),
+                   Err(Error::UnknownUnit {
+                       start: 2, end: 9, unit: "Stunden".to_string(),
+                       value: 2 }));
+    }
+
+    #[test]
+    fn sum() {
+        assert_eq!(sum_durations(vec![]), Ok(Duration::ZERO));
+        assert_eq!(sum_durations(vec![
+            Duration::new(1, 600_000_000),
+            Duration::new(2, 600_000_000),
+        ]), Ok(Duration::new(4, 200_000_000)));
+        let many = (0..500).map(|_| Duration::new(3600, 1));
+        assert_eq!(sum_durations(many), Ok(Duration::new(1_800_000, 500)));
+        assert_eq!(sum_durations(vec![
+            Duration::MAX - Duration::new(1, 0),
+            Duration::new(1, 0),
+        ]), Ok(Duration::MAX));
+        assert_eq!(sum_durations(vec![
+            Duration::MAX - Duration::new(1, 0),
+            Duration::new(1, 0),
+            Duration::new(0, 1),
+        ]), Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(parse_duration_prefix("100ms/op"),
+                   Ok((Duration::new(0, 100_000_000), "/op")));
+        assert_eq!(parse_duration_prefix("1min 5s / request"),
+                   Ok((Duration::new(65, 0), "/ request")));
+        assert_eq!(parse_duration_prefix("2h"),
+                   Ok((Duration::new(7200, 0), "")));
+        assert_eq!(parse_duration_prefix("1s//"),
+                   Ok((Duration::new(1, 0), "//")));
+        assert_eq!(parse_duration_prefix("/op"), Err(Error::Empty));
+        assert_eq!(parse_duration_prefix("5x/op"), Err(Error::UnknownUnit {
+            start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+        assert_eq!(parse_duration("100ms/op"),
+                   Err(Error::InvalidCharacter(5)));
+    }
+
+    #[test]
+    fn u128_nanos() {
+        for s in &["0", "1ns", "1s 5ns", "2h 37min", "1.5y 3.25d",
+                   "0.0000000005s", "1000000000000000000ns",
+                   "18446744073709551615s 999999999ns"]
+        {
+            assert_eq!(parse_duration_u128_nanos(s),
+                       Ok(parse_duration(s).unwrap().as_nanos()), "{}", s);
+        }
+        assert_eq!(parse_duration_u128_nanos("18446744073709551616s"),
+                   Err(Error::NumberOverflow));
+        // each number is still u64, only the total is u128
+        assert_eq!(parse_duration_u128_nanos("18446744073709551616ns"),
+                   Err(Error::NumberOverflow));
+        assert_eq!(parse_duration_u128_nanos("18446744073709551615ns 1ns"),
+                   Ok(18446744073709551616));
+        assert_eq!(parse_duration_u128_nanos("18446744073709551615s 1s"),
+                   Ok(18446744073709551616 * 1_000_000_000));
+        let max_years = u64::MAX as u128 * 31_557_600_000_000_000;
+        assert_eq!(parse_duration_u128_nanos("18446744073709551615y"),
+                   Ok(max_years));
+        let big = format!("{}y", u64::MAX);
+        let many = vec![&big[..]; 600].join(" ");
+        assert_eq!(parse_duration_u128_nanos(&many),
+                   Err(Error::NumberOverflow));
+        assert_eq!(parse_duration_u128_nanos("5x"), Err(Error::UnknownUnit {
+            start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+    }
+
+    #[test]
+    fn si_prefixes() {
+        let mut parser = DurationParser::new();
+        parser.si_prefixes(true);
+        assert_eq!(parser.parse("2ks"), Ok(Duration::new(2000, 0)));
+        assert_eq!(parser.parse("1.5ks 5s"), Ok(Duration::new(1505, 0)));
+        assert_eq!(parser.parse("2ms"), Ok(Duration::new(0, 2_000_000)));
+        assert_eq!(parser.parse("2M"), Ok(Duration::new(2 * 2_630_016, 0)));
+        assert_eq!(parser.parse("2Ms"), Err(Error::UnknownUnit {
+            start: 1, end: 3, unit: "Ms".to_string(), value: 2 }));
+        assert_eq!(parser.parse("2kilos"), Err(Error::UnknownUnit {
+            start: 1, end: 6, unit: "kilos".to_string(), value: 2 }));
+        assert_eq!(DurationParser::new().parse("2ks"),
+                   Err(Error::UnknownUnit {
+                       start: 1, end: 3, unit: "ks".to_string(), value: 2 }));
+    }
+
+    #[test]
+    fn multiple_of() {
+        let parse = parse_duration_multiple_of;
+        assert_eq!(parse("120s", Unit::Minutes), Ok(Duration::new(120, 0)));
+        assert_eq!(parse("1h 5min", Unit::Minutes),
+                   Ok(Duration::new(3900, 0)));
+        assert_eq!(parse("0", Unit::Days), Ok(Duration::new(0, 0)));
+        assert_eq!(parse("1.5s", Unit::Millis),
+                   Ok(Duration::new(1, 500_000_000)));
+        assert_eq!(parse("90s", Unit::Minutes),
+                   Err(Error::NotMultiple(Unit::Minutes)));
+        assert_eq!(parse("1s 1ns", Unit::Micros),
+                   Err(Error::NotMultiple(Unit::Micros)));
+        assert_eq!(parse("5x", Unit::Minutes), Err(Error::UnknownUnit {
+            start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+        assert_eq!(Error::NotMultiple(Unit::Minutes).to_string(),
+                   "value must be a whole number of minutes");
+    }
+
+    #[test]
+    fn max_spans() {
+        let mut parser = DurationParser::new();
+        parser.max_spans(3);
+        assert_eq!(parser.parse("1h 2min 3s"), Ok(Duration::new(3723, 0)));
+        assert_eq!(parser.parse("1h 2min 3s 4ms"), Err(Error::TooManySpans));
+        assert_eq!(parser.parse("0"), Ok(Duration::new(0, 0)));
+        let many = vec!["1s"; 1000].join(" ");
+        assert_eq!(parser.parse(&many), Err(Error::TooManySpans));
+        assert_eq!(DurationParser::new().parse(&many),
+                   Ok(Duration::new(1000, 0)));
+        parser.max_spans(0);
+        assert_eq!(parser.parse("1s"), Err(Error::TooManySpans));
+    }
+
+    #[test]
+    fn prose() {
+        let prose = |secs, nanos| {
+            format_duration_prose(Duration::new(secs, nanos)).to_string()
+        };
+        assert_eq!(prose(0, 0), "0 seconds");
+        assert_eq!(prose(1, 0), "1 second");
+        assert_eq!(prose(7200, 0), "2 hours");
+        assert_eq!(prose(7215, 0), "2 hours and 15 seconds");
+        assert_eq!(prose(9435, 0), "2 hours, 37 minutes and 15 seconds");
+        assert_eq!(prose(86400 + 60, 1_000_001),
+                   "1 day, 1 minute, 1 millisecond and 1 nanosecond");
+        assert_eq!(prose(0, 5), "5 nanoseconds");
+        assert_eq!(humanize(Duration::new(9435, 0), Style::Prose),
+                   "2 hours, 37 minutes and 15 seconds");
+    }
+
+    #[test]
+    fn schedulable() {
+        assert_eq!(parse_duration_schedulable("2h 5min"),
+                   Ok(Duration::new(7500, 0)));
+        assert_eq!(parse_duration_schedulable("100years"),
+                   Ok(Duration::new(3_155_760_000, 0)));
+        let huge = "18446744073709551615s";
+        assert_eq!(parse_duration(huge), Ok(Duration::new(u64::MAX, 0)));
+        assert_eq!(parse_duration_schedulable(huge),
+                   Err(Error::NumberOverflow));
+        assert_eq!(parse_duration_schedulable("5x"), Err(Error::UnknownUnit {
+            start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+    }
+
+    #[test]
+    fn display_with_source() {
+        let show = |src| {
+            parse_duration(src).unwrap_err()
+                .display_with_source(src).to_string()
+        };
+        assert_eq!(show("1h 5xyz"), "unknown time unit \"xyz\" at 4..7");
+        assert_eq!(show("1h 5m$"), "invalid character \"$\" at 5..6");
+        assert_eq!(show("1h\u{a0}\u{e9}"),
+                   "expected number \"\u{e9}\" at 4..6");
+        assert_eq!(show("5min -3s"), "unexpected sign \"-\" at 5..6");
+        assert_eq!(show("1h 5"), "time unit needed, for example 5sec at 4");
+        assert_eq!(show(""), "value was empty");
+        assert_eq!(show("99999999999999999999s"), "number is too large");
+    }
+
+    #[test]
+    fn error_kind() {
+        let unknown = Error::UnknownUnit {
+            start: 0, end: 0, unit: String::new(), value: 1 };
+        let cases = [
+            (Error::InvalidCharacter(0), ErrorKind::Syntax),
+            (Error::NumberExpected(0), ErrorKind::Syntax),
+            (unknown, ErrorKind::Unknown),
+            (Error::NumberOverflow, ErrorKind::Overflow),
+            (Error::Empty, ErrorKind::Empty),
+            (Error::InvalidRange, ErrorKind::Syntax),
+            (Error::KeyValueExpected(0), ErrorKind::Syntax),
+            (Error::UnexpectedSign(0), ErrorKind::Syntax),
+            (Error::NotMultiple(Unit::Seconds), ErrorKind::Syntax),
+            (Error::TooManySpans, ErrorKind::Overflow),
+            (Error::SubsecondNotAllowed, ErrorKind::Syntax),
+            (Error::UnexpectedHex(0), ErrorKind::Syntax),
+        ];
+        for (err, kind) in cases.iter() {
+            assert_eq!(err.kind(), *kind, "{:?}", err);
+        }
+        assert_eq!(parse_duration("").unwrap_err().kind(), ErrorKind::Empty);
+        assert_eq!(parse_duration("5").unwrap_err().kind(),
+                   ErrorKind::Unknown);
+    }
+
+    #[test]
+    fn counts() {
+        let counts = parse_duration_counts("2h 30min 30min").unwrap();
+        assert_eq!(counts.into_iter().collect::<Vec<_>>(),
+                   vec![(Unit::Minutes, 60), (Unit::Hours, 2)]);
+        let counts = parse_duration_counts("1s 2sec 3seconds 4ms").unwrap();
+        assert_eq!(counts.into_iter().collect::<Vec<_>>(),
+                   vec![(Unit::Millis, 4), (Unit::Seconds, 6)]);
+        assert_eq!(parse_duration_counts("0").unwrap().len(), 0);
+        assert_eq!(parse_duration_counts("1h 1.5min"),
+                   Err(Error::InvalidCharacter(4)));
+        assert_eq!(parse_duration_counts("5x"), Err(Error::UnknownUnit {
+            start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+        assert_eq!(parse_duration_counts(
+                       "18446744073709551615ns 18446744073709551615ns"),
+                   Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn percent() {
+        let parse = parse_duration_percent;
+        assert_eq!(parse("50% of 2h"), Ok(Duration::new(3600, 0)));
+        assert_eq!(parse("200% of 30s"), Ok(Duration::new(60, 0)));
+        assert_eq!(parse(" 10%  of  1min 1s "),
+                   Ok(Duration::new(6, 100_000_000)));
+        assert_eq!(parse("0% of 1y"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse("33% of 1ns"), Ok(Duration::new(0, 0)));
+        assert_eq!(parse(""), Err(Error::Empty));
+        assert_eq!(parse("half of 2h"), Err(Error::NumberExpected(0)));
+        assert_eq!(parse("50 of 2h"), Err(Error::InvalidCharacter(2)));
+        assert_eq!(parse("50% off 2h"), Err(Error::InvalidCharacter(4)));
+        assert_eq!(parse("50%of 2h"), Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse("50% of2h"), Err(Error::InvalidCharacter(4)));
+        assert_eq!(parse("50% of 2x"), Err(Error::UnknownUnit {
+            start: 8, end: 9, unit: "x".to_string(), value: 2 }));
+        assert_eq!(parse("50% of "), Err(Error::Empty));
+        assert_eq!(parse("1000000000000000000000% of 18446744073709551615s"),
+                   Err(Error::NumberOverflow));
+        assert_eq!(parse("200% of 18446744073709551615s"),
+                   Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn offset() {
+        let base = Duration::new(600, 0);
+        let p = |s| parse_duration_offset(s, base);
+        assert_eq!(p("base+5m"), Ok(Duration::new(900, 0)));
+        assert_eq!(p("base-30s"), Ok(Duration::new(570, 0)));
+        assert_eq!(p("base"), Ok(base));
+        assert_eq!(p("  base  "), Ok(base));
+        assert_eq!(p(" base + 1h 5m"), Ok(Duration::new(4500, 0)));
+        assert_eq!(p("base-10m"), Ok(Duration::ZERO));
+        assert_eq!(p("5m"), Ok(Duration::new(300, 0)));
+        assert_eq!(p("base-11m"), Err(Error::NumberOverflow));
+        assert_eq!(parse_duration_offset("base+1s", Duration::MAX),
+                   Err(Error::NumberOverflow));
+        assert_eq!(p("base*2"), Err(Error::InvalidCharacter(4)));
+        assert_eq!(p("baseline"), Err(Error::InvalidCharacter(4)));
+        assert_eq!(p("base+"), Err(Error::Empty));
+        assert_eq!(p("base + 5x"), Err(Error::UnknownUnit {
+            start: 8, end: 9, unit: "x".to_string(), value: 5 }));
+        assert_eq!(p("base+-5s"), Err(Error::NumberExpected(5)));
+        assert_eq!(p(""), Err(Error::Empty));
+    }
+
+    #[test]
+    fn significant() {
+        let val = parse_duration("2h 37min 15s 500ms").unwrap();
+        let mut fmt = DurationFormat::new();
+        assert_eq!(fmt.format(val).to_string(), "2h 37m 15s 500ms");
+        fmt.significant(2);
+        assert_eq!(fmt.format(val).to_string(), "2h 37m");
+        assert_eq!(*fmt.format(val).get_ref(), Duration::new(9420, 0));
+        fmt.significant(3);
+        assert_eq!(fmt.format(val).to_string(), "2h 37m 15s");
+        fmt.significant(1);
+        assert_eq!(fmt.format(val).to_string(), "2h");
+        fmt.significant(2);
+        let sparse = parse_duration("2h 15s").unwrap();
+        assert_eq!(fmt.format(sparse).to_string(), "2h");
+        assert_eq!(fmt.format(Duration::ZERO).to_string(), "0s");
+        fmt.style(Style::Long);
+        assert_eq!(fmt.format(val).to_string(), "2 hours 37 minutes");
+        let years = parse_duration("1y 2M 3d 4h").unwrap();
+        assert_eq!(fmt.format(years).to_string(), "1 year 2 months");
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse_duration("0x10s"), Err(Error::UnexpectedHex(0)));
+        assert_eq!(parse_duration("1h 0XFFms"), Err(Error::UnexpectedHex(3)));
+        assert_eq!(parse_duration("0x10s").unwrap_err().to_string(),
+            "hexadecimal number at 0, only decimal numbers are supported");
+        let src = "5min 0xa";
+        let labels = parse_duration(src).unwrap_err().labels(src);
+        assert_eq!(&src[labels[0].0.clone()], "0x");
+        // not a hex prefix
+        assert_eq!(parse_duration("10x5s"), Err(Error::UnknownUnit {
+            start: 2, end: 3, unit: "x".to_string(), value: 10 }));
+        assert_eq!(parse_duration("1.0x5s"), Err(Error::UnknownUnit {
+            start: 3, end: 4, unit: "x".to_string(), value: 1 }));
+        assert_eq!(parse_duration("0xs"), Err(Error::UnknownUnit {
+            start: 1, end: 3, unit: "xs".to_string(), value: 0 }));
+        assert_eq!(parse_duration("0 x1s"), Err(Error::UnknownUnit {
+            start: 2, end: 3, unit: "x".to_string(), value: 0 }));
+    }
+
+    #[test]
+    fn unicode_whitespace() {
+        // U+00A0 no-break space is two bytes, U+3000 ideographic space three
+        assert_eq!(parse_duration("5\u{a0}min"), Ok(Duration::new(300, 0)));
+        assert_eq!(parse_duration("\u{a0}1h 5\u{a0}min\u{3000}3s\u{a0}"),
+                   Ok(Duration::new(3903, 0)));
+        assert_eq!(parse_duration("5\u{2003}min"), Ok(Duration::new(300, 0)));
+        assert_eq!(parse_duration("\u{a0}0\u{a0}"), Ok(Duration::ZERO));
+        assert_eq!(parse_duration("\u{a0}"), Err(Error::Empty));
+        assert_eq!(parse_duration_unit_first("min\u{a0}5"),
+                   Ok(Duration::new(300, 0)));
+        assert_eq!(parse_duration("1h\u{a0}5\u{a0}x"),
+                   Err(Error::UnknownUnit {
+                       start: 7, end: 8, unit: "x".to_string(), value: 5 }));
+        assert_eq!(parse_duration("1h\u{a0}5\u{a0}"),
+                   Err(Error::UnknownUnit {
+                       start: 7, end: 7, unit: "".to_string(), value: 5 }));
+        assert_eq!(parse_duration_with_default("1h\u{a0}500\u{a0}",
+                                               Unit::Millis),
+                   Ok(Duration::new(3600, 500_000_000)));
+    }
+
+    #[test]
+    fn format_scientific() {
+        let f = |secs, nanos, precision| {
+            format_duration_scientific(Duration::new(secs, nanos), precision)
+        };
+        assert_eq!(f(0, 0, 2), "0.00e0 s");
+        assert_eq!(f(1, 0, 2), "1.00e0 s");
+        assert_eq!(f(5, 0, 0), "5e0 s");
+        assert_eq!(f(3600, 0, 3), "3.600e3 s");
+        assert_eq!(f(1, 500_000_000, 2), "1.50e0 s");
+        assert_eq!(f(0, 500_000_000, 2), "5.00e-1 s");
+        assert_eq!(f(0, 1_234_567, 4), "1.2346e-3 s");
+        assert_eq!(f(0, 1, 1), "1.0e-9 s");
+        for &(secs, nanos) in &[(0, 0), (1, 500_000_000), (0, 250_000),
+                                (86400, 0), (0, 1)]
+        {
+            let d = Duration::new(secs, nanos);
+            let s = format_duration_scientific(d, 6);
+            assert_eq!(parse_duration_scientific(&s), Ok(d), "{}", s);
+        }
+    }
+
+    #[test]
+    fn unquoted() {
+        assert_eq!(parse_duration_unquoted("\"30s\""),
+                   Ok(Duration::new(30, 0)));
+        assert_eq!(parse_duration_unquoted("'30s'"), Ok(Duration::new(30, 0)));
+        assert_eq!(parse_duration_unquoted("30s"), Ok(Duration::new(30, 0)));
+        assert_eq!(parse_duration_unquoted("\"30s"),
+                   Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse_duration_unquoted("30s'"),
+                   Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse_duration_unquoted("'30s\""),
+                   Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse_duration_unquoted("\""),
+                   Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse_duration_unquoted("''"), Err(Error::Empty));
+        assert_eq!(parse_duration_unquoted("\"\"30s\"\""),
+                   Err(Error::NumberExpected(1)));
+        assert_eq!(parse_duration_unquoted("'5x'"),
+                   Err(Error::UnknownUnit {
+                       start: 2, end: 3, unit: "x".to_string(), value: 5 }));
+    }
+
+    #[test]
+    fn partial() {
+        assert_eq!(parse_duration_partial("1h 30min"),
+                   (Duration::new(5400, 0), None));
+        assert_eq!(parse_duration_partial("1h 30min 5x"),
+            (Duration::new(5400, 0), Some((Error::UnknownUnit {
+                start: 10, end: 11, unit: "x".to_string(), value: 5 }, 10))));
+        assert_eq!(parse_duration_partial("2h 15m garbage"),
+            (Duration::new(8100, 0), Some((Error::NumberExpected(7), 7))));
+        assert_eq!(parse_duration_partial("2h 1$"),
+            (Duration::new(7200, 0), Some((Error::InvalidCharacter(4), 4))));
+        assert_eq!(parse_duration_partial("oops"),
+            (Duration::ZERO, Some((Error::NumberExpected(0), 0))));
+        assert_eq!(parse_duration_partial(""),
+            (Duration::ZERO, Some((Error::Empty, 0))));
+        let (value, err) = parse_duration_partial(
+            "1s 99999999999999999999999h");
+        assert_eq!(value, Duration::new(1, 0));
+        assert_eq!(err.map(|(e, _)| e), Some(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn total_millis() {
+        for &millis in &[0, 1, 999, 1500, 86_400_000, u64::MAX] {
+            let d = Duration::from_millis(millis);
+            let s = format_duration_total_millis(d);
+            assert_eq!(s, millis.to_string());
+            assert_eq!(parse_millis(&s), Ok(d));
+        }
+        assert_eq!(format_duration_total_millis(Duration::new(1, 999_999)),
+                   "1000");
+        assert_eq!(format_duration_total_millis(Duration::MAX),
+                   "18446744073709551615999");
+        assert_eq!(parse_millis(""), Err(Error::Empty));
+        assert_eq!(parse_millis("15s"), Err(Error::InvalidCharacter(2)));
+        assert_eq!(parse_millis("-1"), Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse_millis(" 1"), Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse_millis("18446744073709551616"),
+                   Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn whole_seconds() {
+        assert_eq!(parse_duration_whole_seconds("5s"),
+                   Ok(Duration::new(5, 0)));
+        assert_eq!(parse_duration_whole_seconds("2min 2000ms"),
+                   Ok(Duration::new(122, 0)));
+        assert_eq!(parse_duration_whole_seconds("500ms"),
+                   Err(Error::SubsecondNotAllowed));
+        assert_eq!(parse_duration_whole_seconds("1s 500ms"),
+                   Err(Error::SubsecondNotAllowed));
+        assert_eq!(parse_duration_whole_seconds("1.5s"),
+                   Err(Error::SubsecondNotAllowed));
+        assert_eq!(parse_duration_whole_seconds("5x"),
+                   Err(Error::UnknownUnit {
+                       start: 1, end: 2, unit: "x".to_string(), value: 5 }));
+        assert_eq!(Error::SubsecondNotAllowed.to_string(),
+                   "value must be a whole number of seconds");
+    }
+
+    #[test]
+    fn from_read() {
+        let input = "1h\r\n30min\n5x\n\n2s";
+        let result = parse_durations_from_read(Cursor::new(input))
+            .collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(result, vec![
+            Ok(Duration::new(3600, 0)),
+            Ok(Duration::new(1800, 0)),
+            Err(Error::UnknownUnit {
+                start: 1, end: 2, unit: "x".to_string(), value: 5 }),
+            Err(Error::Empty),
+            Ok(Duration::new(2, 0)),
+        ]);
+        let mut invalid = parse_durations_from_read(Cursor::new(b"1s\n\xff"));
+        assert_eq!(invalid.next().unwrap().unwrap(), Ok(Duration::new(1, 0)));
+        assert!(invalid.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn negligible() {
+        let ms = Duration::from_millis(1);
+        assert!(is_negligible(ms - Duration::new(0, 1), ms));
+        assert!(!is_negligible(ms, ms));
+        assert!(!is_negligible(ms + Duration::new(0, 1), ms));
+        assert!(!is_negligible(Duration::ZERO, Duration::ZERO));
+    }
+
+    #[test]
+    fn with_default() {
+        let ms = |s| parse_duration_with_default(s, Unit::Millis);
+        assert_eq!(ms("500"), Ok(Duration::from_millis(500)));
+        assert_eq!(ms("5s"), Ok(Duration::new(5, 0)));
+        assert_eq!(ms("1s 500 "), Ok(Duration::from_millis(1500)));
+        assert_eq!(ms("1.5"), Ok(Duration::from_micros(1500)));
+        assert_eq!(ms("0"), Ok(Duration::ZERO));
+        assert_eq!(parse_duration_with_default("2", Unit::Hours),
+                   Ok(Duration::new(7200, 0)));
+        assert!(ms("500x").is_err());
+        assert!(ms("").is_err());
+        assert_eq!(parse_duration("500"), Err(Error::UnknownUnit {
+            start: 3, end: 3, unit: "".to_string(), value: 500,
+        }));
+    }
+
+    #[test]
+    fn days_hours() {
+        let dh = |secs| format_duration_dh(Duration::new(secs, 0)).to_string();
+        assert_eq!(dh(5 * 86400 + 3 * 3600 + 59 * 60), "5d 3h");
+        assert_eq!(dh(400 * 86400), "400d");
+        assert_eq!(dh(86400 + 59), "1d");
+        assert_eq!(dh(3 * 3600 + 1), "3h");
+        assert_eq!(dh(3599), "0h");
+        assert_eq!(dh(0), "0h");
+        assert_eq!(humanize(Duration::new(90000, 0), Style::DaysHours),
+                   "1d 1h");
+    }
+
+    #[test]
+    fn json_string() {
+        assert_eq!(format_duration(Duration::new(0, 0)).to_json_string(),
+            "\"0s\"");
+        assert_eq!(format_duration(Duration::new(90061, 5)).to_json_string(),
+            "\"1day 1h 1m 1s 5ns\"");
+    }
+
     #[test]
     fn all_86400_seconds() {
         for second in 0..86400 {  // scan leap year and non-leap year
@@ -425,6 +3333,10 @@ mod test {
             Err(Error::NumberOverflow));
         assert_eq!(parse_duration("100000000000000ms"),
             Err(Error::NumberOverflow));
+        assert_eq!(parse_duration("10000000000000000000cs"),
+            Err(Error::NumberOverflow));
+        assert_eq!(parse_duration("1000000000000000000ds"),
+            Err(Error::NumberOverflow));
 
         assert_eq!(parse_duration("100000000000000000000s"),
             Err(Error::NumberOverflow));
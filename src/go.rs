@@ -0,0 +1,224 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::duration::{add_sec_nsec, fraction, scan_decimal, Error, Rounding};
+use crate::duration::{write_nanos_fraction};
+use crate::unit::{unit_nanos, Unit};
+
+/// Parse duration in the format of Go's `time.ParseDuration` `1h30m0.5s`
+///
+/// Spans have no spaces in between, any of them may have a fractional
+/// part. Units are `ns`, `us` (or `µs`), `ms`, `s`, `m` and `h`. Note that
+/// `m` is minutes and there are no days or weeks. A lone `0` needs no
+/// unit.
+///
+/// The value may be prefixed by a sign. As `std::time::Duration` can't be
+/// negative, only zero is accepted after `-`, other negative values are
+/// rejected with [`UnexpectedSign`](crate::DurationError::UnexpectedSign).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_go_duration;
+///
+/// assert_eq!(parse_go_duration("1h30m"), Ok(Duration::new(5400, 0)));
+/// assert_eq!(parse_go_duration("300µs"), Ok(Duration::from_micros(300)));
+/// ```
+pub fn parse_go_duration(s: &str) -> Result<Duration, Error> {
+    let b = s.as_bytes();
+    let (negative, mut idx) = match b.first() {
+        None => return Err(Error::Empty),
+        Some(b'-') => (true, 1),
+        Some(b'+') => (false, 1),
+        Some(_) => (false, 0),
+    };
+    if &b[idx..] == b"0" {
+        return Ok(Duration::ZERO);
+    }
+    if idx == b.len() {
+        return Err(Error::NumberExpected(idx));
+    }
+    let mut total: (u64, u64) = (0, 0);
+    while idx < b.len() {
+        let start = idx;
+        let (n, frac, digits) = scan_decimal(b, &mut idx, b".")?;
+        if digits == 0 && frac.1 == 0 {
+            return Err(Error::NumberExpected(start));
+        }
+        let unit_start = idx;
+        while idx < b.len() && b[idx] != b'.' && !b[idx].is_ascii_digit() {
+            idx += 1;
+        }
+        let unit = match &s[unit_start..idx] {
+            "ns" => Unit::Nanos,
+            // U+00B5 micro sign and U+03BC greek small letter mu
+            "us" | "\u{b5}s" | "\u{3bc}s" => Unit::Micros,
+            "ms" => Unit::Millis,
+            "s" => Unit::Seconds,
+            "m" => Unit::Minutes,
+            "h" => Unit::Hours,
+            unit => {
+                return Err(Error::UnknownUnit {
+                    start: unit_start,
+                    end: idx,
+                    unit: unit.to_string(),
+                    value: n,
+                });
+            }
+        };
+        total = add_sec_nsec(total, fraction(n, frac, unit_nanos(unit),
+                                             Rounding::Truncate)?)?;
+    }
+    if negative && total != (0, 0) {
+        return Err(Error::UnexpectedSign(0));
+    }
+    Ok(Duration::new(total.0, total.1 as u32))
+}
+
+/// Formats duration the same way as Go's `Duration.String` `1h30m0s`
+///
+/// Durations shorter than a second use the smallest unit that keeps the
+/// integer part non-zero (`300ms`, `1.5µs`, using U+00B5 micro sign).
+/// Longer ones are written as hours, minutes and seconds, where seconds
+/// are always present and leading zero units are omitted (`1m0.5s`).
+/// Trailing zeros of the fraction are trimmed. Zero is `0s`.
+///
+/// The output is accepted by [`parse_go_duration`](parse_go_duration) and
+/// by Go itself (as long as it fits Go's 290 years).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::format_go_duration;
+///
+/// assert_eq!(format_go_duration(Duration::new(5400, 0)), "1h30m0s");
+/// assert_eq!(format_go_duration(Duration::from_millis(1500)), "1.5s");
+/// assert_eq!(format_go_duration(Duration::from_millis(300)), "300ms");
+/// ```
+pub fn format_go_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let nanos = d.subsec_nanos();
+    let mut buf = String::new();
+    if secs == 0 {
+        match nanos {
+            0 => buf.push_str("0s"),
+            1..=999 => write!(buf, "{}ns", nanos).unwrap(),
+            1_000..=999_999 => {
+                write_fraction(&mut buf, nanos / 1_000, nanos % 1_000, 3);
+                buf.push_str("\u{b5}s");
+            }
+            _ => {
+                write_fraction(&mut buf, nanos / 1_000_000,
+                               nanos % 1_000_000, 6);
+                buf.push_str("ms");
+            }
+        }
+        return buf;
+    }
+    if secs >= 3600 {
+        write!(buf, "{}h", secs / 3600).unwrap();
+    }
+    if secs >= 60 {
+        write!(buf, "{}m", secs % 3600 / 60).unwrap();
+    }
+    write_fraction(&mut buf, (secs % 60) as u32, nanos, 9);
+    buf.push('s');
+    buf
+}
+
+fn write_fraction(buf: &mut String, int: u32, frac: u32, digits: u32) {
+    write!(buf, "{}", int).unwrap();
+    write_nanos_fraction(buf, frac * 10u32.pow(9 - digits)).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use rand::Rng;
+
+    use super::{parse_go_duration, format_go_duration};
+    use crate::duration::Error;
+
+    #[test]
+    fn parse() {
+        // examples from the documentation of `time.ParseDuration`
+        assert_eq!(parse_go_duration("10h"), Ok(Duration::new(36000, 0)));
+        assert_eq!(parse_go_duration("1h10m10s"), Ok(Duration::new(4210, 0)));
+        assert_eq!(parse_go_duration("2h45m"), Ok(Duration::new(9900, 0)));
+        assert_eq!(parse_go_duration("1h15m30.918273645s"),
+                   Ok(Duration::new(4530, 918_273_645)));
+        assert_eq!(parse_go_duration("1.5h"), Ok(Duration::new(5400, 0)));
+        assert_eq!(parse_go_duration("300ms"), Ok(Duration::from_millis(300)));
+        assert_eq!(parse_go_duration("1\u{b5}s"), Ok(Duration::from_micros(1)));
+        assert_eq!(parse_go_duration("1\u{3bc}s"),
+                   Ok(Duration::from_micros(1)));
+        assert_eq!(parse_go_duration("1us"), Ok(Duration::from_micros(1)));
+        assert_eq!(parse_go_duration("1ns"), Ok(Duration::new(0, 1)));
+        assert_eq!(parse_go_duration("1h30m"), Ok(Duration::new(5400, 0)));
+        assert_eq!(parse_go_duration(".5s"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_go_duration("5.s"), Ok(Duration::new(5, 0)));
+        assert_eq!(parse_go_duration("+5s"), Ok(Duration::new(5, 0)));
+        assert_eq!(parse_go_duration("0"), Ok(Duration::ZERO));
+        assert_eq!(parse_go_duration("-0"), Ok(Duration::ZERO));
+        assert_eq!(parse_go_duration("-0s"), Ok(Duration::ZERO));
+        assert_eq!(parse_go_duration("1m1m"), Ok(Duration::new(120, 0)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_go_duration(""), Err(Error::Empty));
+        assert_eq!(parse_go_duration("-"), Err(Error::NumberExpected(1)));
+        assert_eq!(parse_go_duration("-1.5h"), Err(Error::UnexpectedSign(0)));
+        assert_eq!(parse_go_duration(".s"), Err(Error::NumberExpected(0)));
+        assert_eq!(parse_go_duration("1h s"),
+                   Err(Error::UnknownUnit {
+                       start: 1, end: 4, unit: "h s".to_string(), value: 1 }));
+        assert_eq!(parse_go_duration("1d"),
+                   Err(Error::UnknownUnit {
+                       start: 1, end: 2, unit: "d".to_string(), value: 1 }));
+        assert_eq!(parse_go_duration("10"),
+                   Err(Error::UnknownUnit {
+                       start: 2, end: 2, unit: "".to_string(), value: 10 }));
+        assert_eq!(parse_go_duration("99999999999999999999s"),
+                   Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn format() {
+        let f = |secs, nanos| format_go_duration(Duration::new(secs, nanos));
+        assert_eq!(f(0, 0), "0s");
+        assert_eq!(f(0, 1), "1ns");
+        assert_eq!(f(0, 999), "999ns");
+        assert_eq!(f(0, 1_500), "1.5\u{b5}s");
+        assert_eq!(f(0, 300_000), "300\u{b5}s");
+        assert_eq!(f(0, 300_000_000), "300ms");
+        assert_eq!(f(0, 1_000_001), "1.000001ms");
+        assert_eq!(f(1, 500_000_000), "1.5s");
+        assert_eq!(f(59, 0), "59s");
+        assert_eq!(f(60, 0), "1m0s");
+        assert_eq!(f(60, 500_000_000), "1m0.5s");
+        assert_eq!(f(3600, 0), "1h0m0s");
+        assert_eq!(f(5400, 0), "1h30m0s");
+        assert_eq!(f(9900, 0), "2h45m0s");
+        assert_eq!(f(4530, 918_273_645), "1h15m30.918273645s");
+        assert_eq!(f(360_000, 1), "100h0m0.000000001s");
+    }
+
+    #[test]
+    fn roundtrip() {
+        for &(secs, nanos) in &[(0, 0), (0, 1), (0, 1_500), (0, 999_999_999),
+                                (1, 0), (3600, 0), (86400, 1)] {
+            let d = Duration::new(secs, nanos);
+            assert_eq!(parse_go_duration(&format_go_duration(d)), Ok(d));
+        }
+        for _ in 0..10000 {
+            let sec = rand::thread_rng().gen_range(0, 10_000_000_000);
+            let nanos = rand::thread_rng().gen_range(0, 1_000_000_000);
+            let d = Duration::new(sec, nanos);
+            assert_eq!(parse_go_duration(&format_go_duration(d)), Ok(d));
+        }
+    }
+}
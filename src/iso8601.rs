@@ -0,0 +1,260 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::duration::{fraction, parse_duration, Error, Rounding};
+use crate::duration::{add_sec_nsec, scan_decimal, write_nanos_fraction};
+use crate::unit::{unit_nanos, Unit};
+
+/// Parse ISO 8601 duration `P1DT2H30M`
+///
+/// Supported designators are `Y`, `M`, `W`, `D` in the date part and `H`,
+/// `M`, `S` in the time part (after `T`). Each may appear once, in this
+/// order. Years and months have the same length as in
+/// [`parse_duration`](crate::parse_duration): 365.25 and 30.44 days. Any
+/// component may have a fractional part separated by `.` or `,`.
+///
+/// Negative durations are not supported.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_iso8601_duration;
+///
+/// assert_eq!(parse_iso8601_duration("PT1H30M"), Ok(Duration::new(5400, 0)));
+/// assert_eq!(parse_iso8601_duration("P1DT0.5S"),
+///            Ok(Duration::new(86400, 500_000_000)));
+/// ```
+pub fn parse_iso8601_duration(s: &str) -> Result<Duration, Error> {
+    let b = s.as_bytes();
+    match b.first() {
+        None => return Err(Error::Empty),
+        Some(b'P') => {}
+        Some(_) => return Err(Error::InvalidCharacter(0)),
+    }
+    let mut idx = 1;
+    let mut time = false;
+    // index in the list of allowed designators, to check their order
+    let mut next_designator = 0;
+    let mut total: (u64, u64) = (0, 0);
+    let mut components = 0;
+    while idx < b.len() {
+        if b[idx] == b'T' && !time {
+            time = true;
+            next_designator = 4;
+            idx += 1;
+            if idx == b.len() {
+                return Err(Error::NumberExpected(idx));
+            }
+            continue;
+        }
+        let start = idx;
+        let (n, frac, digits) = scan_decimal(b, &mut idx, b".,")?;
+        if digits == 0 {
+            return Err(Error::NumberExpected(start));
+        }
+        let designator = match b.get(idx) {
+            Some(&c) => c,
+            None => {
+                return Err(Error::UnknownUnit {
+                    start: idx, end: idx, unit: String::new(), value: n,
+                });
+            }
+        };
+        let pos = DESIGNATORS[next_designator..].iter()
+            .position(|&(d, is_time, _)| d == designator && is_time == time);
+        let unit = match pos {
+            Some(pos) => {
+                let (_, _, unit) = DESIGNATORS[next_designator + pos];
+                next_designator += pos + 1;
+                unit
+            }
+            None if designator.is_ascii_alphabetic() => {
+                return Err(Error::UnknownUnit {
+                    start: idx,
+                    end: idx + 1,
+                    unit: (designator as char).to_string(),
+                    value: n,
+                });
+            }
+            None => return Err(Error::InvalidCharacter(idx)),
+        };
+        idx += 1;
+        total = add_sec_nsec(total, fraction(n, frac, unit_nanos(unit),
+                                             Rounding::Truncate)?)?;
+        components += 1;
+    }
+    if components == 0 {
+        return Err(Error::NumberExpected(b.len()));
+    }
+    Ok(Duration::new(total.0, total.1 as u32))
+}
+
+/// Parse either ISO 8601 duration `PT1H30M` or human-friendly one `1h 30m`
+///
+/// Strings starting with `P` are parsed by
+/// [`parse_iso8601_duration`](parse_iso8601_duration), others by
+/// [`parse_duration`](crate::parse_duration). There is no ambiguity, as
+/// human-friendly durations start with a number.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_duration_flexible;
+///
+/// assert_eq!(parse_duration_flexible("PT1H30M"), Ok(Duration::new(5400, 0)));
+/// assert_eq!(parse_duration_flexible("1h30m"), Ok(Duration::new(5400, 0)));
+/// ```
+pub fn parse_duration_flexible(s: &str) -> Result<Duration, Error> {
+    if s.starts_with('P') {
+        parse_iso8601_duration(s)
+    } else {
+        parse_duration(s)
+    }
+}
+
+/// Designator, whether it's in time part, and the unit
+const DESIGNATORS: [(u8, bool, Unit); 7] = [
+    (b'Y', false, Unit::Years),
+    (b'M', false, Unit::Months),
+    (b'W', false, Unit::Weeks),
+    (b'D', false, Unit::Days),
+    (b'H', true, Unit::Hours),
+    (b'M', true, Unit::Minutes),
+    (b'S', true, Unit::Seconds),
+];
+
+/// Formats duration as ISO 8601 `P1DT2H30M`
+///
+/// Only days, hours, minutes and seconds are used, as years and months
+/// have no fixed length in ISO 8601.
+pub(crate) fn format_iso8601(val: Duration) -> String {
+    let secs = val.as_secs();
+    let nanos = val.subsec_nanos();
+    let days = secs / 86400;
+    let hours = secs % 86400 / 3600;
+    let minutes = secs % 3600 / 60;
+    let seconds = secs % 60;
+
+    let mut buf = String::from("P");
+    if days > 0 {
+        write!(buf, "{}D", days).unwrap();
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || nanos > 0 || days == 0 {
+        buf.push('T');
+    }
+    if hours > 0 {
+        write!(buf, "{}H", hours).unwrap();
+    }
+    if minutes > 0 {
+        write!(buf, "{}M", minutes).unwrap();
+    }
+    if nanos > 0 {
+        write!(buf, "{}", seconds).unwrap();
+        write_nanos_fraction(&mut buf, nanos).unwrap();
+        buf.push('S');
+    } else if seconds > 0 || secs == 0 {
+        write!(buf, "{}S", seconds).unwrap();
+    }
+    buf
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use rand::Rng;
+
+    use super::{parse_iso8601_duration, format_iso8601};
+    use super::{parse_duration_flexible};
+    use crate::duration::Error;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_iso8601_duration("PT1H30M"),
+                   Ok(Duration::new(5400, 0)));
+        assert_eq!(parse_iso8601_duration("P1W"),
+                   Ok(Duration::new(604_800, 0)));
+        assert_eq!(parse_iso8601_duration("P1Y2M"),
+                   Ok(Duration::new(31_557_600 + 2 * 2_630_016, 0)));
+        assert_eq!(parse_iso8601_duration("P1DT2H3M4.5S"),
+                   Ok(Duration::new(93784, 500_000_000)));
+        assert_eq!(parse_iso8601_duration("PT0,25S"),
+                   Ok(Duration::new(0, 250_000_000)));
+        assert_eq!(parse_iso8601_duration("PT1.5M"),
+                   Ok(Duration::new(90, 0)));
+        assert_eq!(parse_iso8601_duration("PT0S"), Ok(Duration::new(0, 0)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_iso8601_duration(""), Err(Error::Empty));
+        assert_eq!(parse_iso8601_duration("1H"),
+                   Err(Error::InvalidCharacter(0)));
+        assert_eq!(parse_iso8601_duration("P"), Err(Error::NumberExpected(1)));
+        assert_eq!(parse_iso8601_duration("PT"),
+                   Err(Error::NumberExpected(2)));
+        assert_eq!(parse_iso8601_duration("P1H"),
+                   Err(Error::UnknownUnit {
+                       start: 2, end: 3, unit: "H".to_string(), value: 1 }));
+        assert_eq!(parse_iso8601_duration("PT1H1H"),
+                   Err(Error::UnknownUnit {
+                       start: 5, end: 6, unit: "H".to_string(), value: 1 }));
+        assert_eq!(parse_iso8601_duration("PT1M1H"),
+                   Err(Error::UnknownUnit {
+                       start: 5, end: 6, unit: "H".to_string(), value: 1 }));
+        assert_eq!(parse_iso8601_duration("PT1"),
+                   Err(Error::UnknownUnit {
+                       start: 3, end: 3, unit: "".to_string(), value: 1 }));
+        assert_eq!(parse_iso8601_duration("PT1H-"),
+                   Err(Error::NumberExpected(4)));
+        assert_eq!(parse_iso8601_duration("PT1-"),
+                   Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse_iso8601_duration("P100000000000000000000D"),
+                   Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn flexible() {
+        for &(iso, human) in &[
+            ("PT1H30M", "1h30m"),
+            ("P1DT2H", "1day 2h"),
+            ("PT0.5S", "500ms"),
+            ("P1W", "7d"),
+            ("PT0S", "0"),
+        ] {
+            assert_eq!(parse_duration_flexible(iso),
+                       parse_duration_flexible(human));
+            assert!(parse_duration_flexible(iso).is_ok(), "{}", iso);
+        }
+        assert_eq!(parse_duration_flexible("P"), Err(Error::NumberExpected(1)));
+        assert_eq!(parse_duration_flexible("PT1X"),
+                   Err(Error::UnknownUnit {
+                       start: 3, end: 4, unit: "X".to_string(), value: 1 }));
+        assert_eq!(parse_duration_flexible("pt1h"),
+                   Err(Error::NumberExpected(0)));
+        assert_eq!(parse_duration_flexible(""), Err(Error::Empty));
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_iso8601(Duration::new(0, 0)), "PT0S");
+        assert_eq!(format_iso8601(Duration::new(5400, 0)), "PT1H30M");
+        assert_eq!(format_iso8601(Duration::new(86400, 0)), "P1D");
+        assert_eq!(format_iso8601(Duration::new(90061, 0)), "P1DT1H1M1S");
+        assert_eq!(format_iso8601(Duration::new(86400, 500_000_000)),
+                   "P1DT0.5S");
+        assert_eq!(format_iso8601(Duration::new(0, 1)), "PT0.000000001S");
+    }
+
+    #[test]
+    fn roundtrip() {
+        for _ in 0..10000 {
+            let sec = rand::thread_rng().gen_range(0, 253_370_764_800);
+            let nanos = rand::thread_rng().gen_range(0, 1_000_000_000);
+            let d = Duration::new(sec, nanos);
+            assert_eq!(parse_iso8601_duration(&format_iso8601(d)), Ok(d));
+        }
+    }
+}
@@ -10,6 +10,11 @@
 //! Timestamp parsing/formatting is super-fast because format is basically
 //! fixed.
 //!
+//! Optional features:
+//!
+//! * `localtime` -- enables `format_rfc3339_local` which formats timestamp
+//!   in the system timezone (pulls in `chrono` to read timezone settings)
+//!
 //! See [humantime-serde] for serde integration (previous crate [serde-humantime] looks unmaintained).
 //!
 //! [serde-humantime]: https://docs.rs/serde-humantime/0.1.1/serde_humantime/
@@ -22,13 +27,60 @@
 mod duration;
 mod wrapper;
 mod date;
+mod range;
+mod iso8601;
+mod unit;
+mod classify;
+mod go;
+mod protobuf;
 
 pub use self::duration::{parse_duration, Error as DurationError};
-pub use self::duration::{format_duration, FormattedDuration};
-pub use self::wrapper::{Duration, Timestamp};
+pub use self::duration::{ErrorKind as DurationErrorKind};
+pub use self::duration::{parse_duration_verbose, parse_duration_map};
+pub use self::duration::{parse_duration_with_meta, parse_duration_scientific};
+pub use self::duration::{parse_calendar_duration, CalendarDuration};
+pub use self::duration::{parse_duration_canonical, parse_duration_unit_first};
+pub use self::duration::{parse_duration_prefix, parse_duration_u128_nanos};
+pub use self::duration::{parse_duration_multiple_of, parse_duration_schedulable};
+pub use self::duration::{parse_duration_counts, parse_duration_percent};
+pub use self::duration::{parse_duration_grouped, parse_duration_after_sep};
+pub use self::duration::{breakdown, DurationParts};
+pub use self::duration::{DurationParser, Rounding};
+pub use self::duration::{format_duration, duration_string, FormattedDuration};
+pub use self::duration::{DurationFormat, parse_duration_offset};
+pub use self::duration::{humanize, Style, sum_durations, is_negligible};
+pub use self::duration::{format_duration_prose, format_duration_dh};
+pub use self::duration::{parse_duration_with_default};
+pub use self::duration::{parse_durations_from_read};
+pub use self::duration::{parse_duration_whole_seconds};
+pub use self::duration::{format_duration_total_millis, parse_millis};
+pub use self::duration::{parse_duration_partial, parse_duration_unquoted};
+pub use self::duration::{format_duration_scientific};
+pub use self::wrapper::{Duration, FlexibleDuration, Timestamp};
+pub use self::wrapper::{SourcedDuration};
 pub use self::date::{parse_rfc3339, parse_rfc3339_weak, Error as TimestampError};
+pub use self::date::{rfc3339_equivalent, parse_rfc3339_trim, approx_eq};
+pub use self::date::{parse_rfc3339_loose, round_to, round_to_nearest};
+pub use self::date::{parse_rfc3339_2digit_year};
+pub use self::date::{format_relative_or_absolute, age_years};
+pub use self::date::{days_between, format_date, format_weekday};
+pub use self::date::{clamp_to_range, time_between};
+pub use self::date::{parse_rfc3339_since_epoch, parse_time_of_day};
+pub use self::date::{parse_srt_timecode, parse_vtt_timecode};
+pub use self::date::{rfc3339_min_len, RFC3339_MAX_LEN};
+pub use self::date::{MIN_YEAR, MAX_YEAR, MAX_TIMESTAMP_SECS};
 pub use self::date::{
     format_rfc3339, format_rfc3339_micros, format_rfc3339_millis, format_rfc3339_nanos,
     format_rfc3339_seconds,
 };
-pub use self::date::{Rfc3339Timestamp};
+pub use self::date::{Rfc3339Timestamp, timestamp_string};
+pub use self::date::{format_rfc3339_space, Precision};
+#[cfg(feature="localtime")]
+pub use self::date::{format_rfc3339_local};
+pub use self::range::{DurationRange, TimestampRange};
+pub use self::iso8601::{parse_iso8601_duration, parse_duration_flexible};
+pub use self::go::{parse_go_duration, format_go_duration};
+pub use self::protobuf::{format_protobuf_duration, parse_protobuf_duration};
+pub use self::unit::{unit_nanos, dominant_unit, supported_units, Unit};
+pub use self::unit::{UnitTable};
+pub use self::classify::{classify, Kind};
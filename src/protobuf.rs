@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use crate::duration::{write_nanos_fraction, Error};
+
+/// Formats duration as in JSON mapping of protobuf `Duration` `3.5s`
+///
+/// This is seconds with up to nine fractional digits followed by `s`.
+/// Trailing zeros of the fraction are trimmed, and the dot is omitted for
+/// whole seconds, so zero is `0s`. Parse it back with
+/// [`parse_protobuf_duration`](parse_protobuf_duration).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::format_protobuf_duration;
+///
+/// assert_eq!(format_protobuf_duration(Duration::new(3, 1)),
+///            "3.000000001s");
+/// assert_eq!(format_protobuf_duration(Duration::new(60, 0)), "60s");
+/// ```
+pub fn format_protobuf_duration(d: Duration) -> String {
+    let mut buf = d.as_secs().to_string();
+    write_nanos_fraction(&mut buf, d.subsec_nanos()).unwrap();
+    buf.push('s');
+    buf
+}
+
+/// Parse duration in JSON mapping of protobuf `Duration` `3.5s`
+///
+/// The value is decimal seconds with up to nine fractional digits and a
+/// mandatory `s` suffix. More fractional digits are rejected with
+/// [`InvalidCharacter`](crate::DurationError::InvalidCharacter) pointing to
+/// the tenth digit, as protobuf durations have nanosecond precision.
+///
+/// The value may be prefixed by a sign. As `std::time::Duration` can't be
+/// negative, only zero is accepted after `-`, other negative values are
+/// rejected with [`UnexpectedSign`](crate::DurationError::UnexpectedSign).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::parse_protobuf_duration;
+///
+/// assert_eq!(parse_protobuf_duration("3.000000001s"),
+///            Ok(Duration::new(3, 1)));
+/// ```
+pub fn parse_protobuf_duration(s: &str) -> Result<Duration, Error> {
+    let (negative, unsigned) = match s.as_bytes().first() {
+        None => return Err(Error::Empty),
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        Some(_) => (false, s),
+    };
+    let sign_len = s.len() - unsigned.len();
+    let value = parse_unsigned(unsigned).map_err(|e| e.offset_by(sign_len))?;
+    if negative && value != Duration::ZERO {
+        return Err(Error::UnexpectedSign(0));
+    }
+    Ok(value)
+}
+
+fn parse_unsigned(s: &str) -> Result<Duration, Error> {
+    let number = s.strip_suffix('s').unwrap_or(s);
+    let (int, frac) = match number.find('.') {
+        Some(dot) => (&number[..dot], Some((dot + 1, &number[dot + 1..]))),
+        None => (number, None),
+    };
+    if int.is_empty() {
+        return Err(Error::NumberExpected(0));
+    }
+    if let Some(off) = int.find(|c: char| !c.is_ascii_digit()) {
+        return Err(Error::InvalidCharacter(off));
+    }
+    let secs = int.parse().map_err(|_| Error::NumberOverflow)?;
+    let mut nanos = 0;
+    if let Some((start, frac)) = frac {
+        if frac.is_empty() {
+            return Err(Error::NumberExpected(start));
+        }
+        for (idx, c) in frac.char_indices() {
+            if !c.is_ascii_digit() || idx >= 9 {
+                return Err(Error::InvalidCharacter(start + idx));
+            }
+        }
+        nanos = frac.parse::<u32>().map_err(|_| Error::NumberOverflow)?
+            * 10u32.pow(9 - frac.len() as u32);
+    }
+    if number.len() == s.len() {
+        return Err(Error::UnknownUnit {
+            start: s.len(),
+            end: s.len(),
+            unit: String::new(),
+            value: secs,
+        });
+    }
+    Ok(Duration::new(secs, nanos))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use rand::Rng;
+
+    use super::{format_protobuf_duration, parse_protobuf_duration};
+    use crate::duration::Error;
+
+    #[test]
+    fn format() {
+        let f = |secs, nanos| {
+            format_protobuf_duration(Duration::new(secs, nanos))
+        };
+        assert_eq!(f(0, 0), "0s");
+        assert_eq!(f(3, 0), "3s");
+        assert_eq!(f(3, 1), "3.000000001s");
+        assert_eq!(f(0, 500_000_000), "0.5s");
+        assert_eq!(f(1, 10_000_000), "1.01s");
+        assert_eq!(f(86400, 123_456_789), "86400.123456789s");
+        assert_eq!(f(u64::MAX, 999_999_999),
+                   "18446744073709551615.999999999s");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_protobuf_duration("3.000000001s"),
+                   Ok(Duration::new(3, 1)));
+        assert_eq!(parse_protobuf_duration("0.5s"),
+                   Ok(Duration::new(0, 500_000_000)));
+        assert_eq!(parse_protobuf_duration("3s"), Ok(Duration::new(3, 0)));
+        assert_eq!(parse_protobuf_duration("3.120s"),
+                   Ok(Duration::new(3, 120_000_000)));
+        assert_eq!(parse_protobuf_duration("+1.5s"),
+                   Ok(Duration::new(1, 500_000_000)));
+        assert_eq!(parse_protobuf_duration("-0s"), Ok(Duration::ZERO));
+        assert_eq!(parse_protobuf_duration("-0.000s"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_protobuf_duration(""), Err(Error::Empty));
+        assert_eq!(parse_protobuf_duration("3.5"), Err(Error::UnknownUnit {
+            start: 3, end: 3, unit: String::new(), value: 3 }));
+        assert_eq!(parse_protobuf_duration("+3"), Err(Error::UnknownUnit {
+            start: 2, end: 2, unit: String::new(), value: 3 }));
+        assert_eq!(parse_protobuf_duration("-1.5s"),
+                   Err(Error::UnexpectedSign(0)));
+        assert_eq!(parse_protobuf_duration("s"), Err(Error::NumberExpected(0)));
+        assert_eq!(parse_protobuf_duration("+s"),
+                   Err(Error::NumberExpected(1)));
+        assert_eq!(parse_protobuf_duration(".5s"),
+                   Err(Error::NumberExpected(0)));
+        assert_eq!(parse_protobuf_duration("5.s"),
+                   Err(Error::NumberExpected(2)));
+        assert_eq!(parse_protobuf_duration("0.1234567891s"),
+                   Err(Error::InvalidCharacter(11)));
+        assert_eq!(parse_protobuf_duration("+1 s"),
+                   Err(Error::InvalidCharacter(2)));
+        assert_eq!(parse_protobuf_duration("1.5ms"),
+                   Err(Error::InvalidCharacter(3)));
+        assert_eq!(parse_protobuf_duration("18446744073709551616s"),
+                   Err(Error::NumberOverflow));
+    }
+
+    #[test]
+    fn roundtrip() {
+        for &(secs, nanos) in &[(0, 0), (3, 0), (3, 1), (0, 500_000_000),
+                                (u64::MAX, 999_999_999)] {
+            let d = Duration::new(secs, nanos);
+            assert_eq!(parse_protobuf_duration(&format_protobuf_duration(d)),
+                       Ok(d));
+        }
+        for _ in 0..10000 {
+            let sec = rand::thread_rng().gen_range(0, 253_370_764_800);
+            let nanos = rand::thread_rng().gen_range(0, 1_000_000_000);
+            let d = Duration::new(sec, nanos);
+            assert_eq!(parse_protobuf_duration(&format_protobuf_duration(d)),
+                       Ok(d));
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::iter;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::date::{self, parse_rfc3339};
+use crate::duration::{self, parse_duration};
+use crate::wrapper;
+
+/// A range of durations that has `FromStr` implementation
+///
+/// The format is two durations separated by `..`, e.g. `1h..2h` or
+/// `500ms..1500ms`. Each side uses format of `parse_duration`. The lower
+/// bound must not be larger than the upper one.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// let range = "500ms..1500ms".parse::<humantime::DurationRange>().unwrap();
+/// assert_eq!(range.low(), Duration::from_millis(500));
+/// assert_eq!(range.high(), Duration::from_millis(1500));
+/// assert_eq!(range.sample(0.5), Duration::from_millis(1000));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct DurationRange {
+    low: Duration,
+    high: Duration,
+}
+
+impl DurationRange {
+    /// Returns lower bound of the range (inclusive)
+    pub fn low(&self) -> Duration {
+        self.low
+    }
+    /// Returns upper bound of the range (inclusive)
+    pub fn high(&self) -> Duration {
+        self.high
+    }
+    /// Returns a duration at `ratio` position within the range
+    ///
+    /// The `ratio` of `0.0` returns `low()` and `1.0` returns `high()`,
+    /// values outside of this interval are clamped. Pass a random number
+    /// from `0.0..=1.0` to get a random jitter.
+    pub fn sample(&self, ratio: f64) -> Duration {
+        let ratio = if ratio.is_nan() { 0.0 } else { ratio.clamp(0.0, 1.0) };
+        self.low + (self.high - self.low).mul_f64(ratio)
+    }
+}
+
+impl FromStr for DurationRange {
+    type Err = duration::Error;
+    fn from_str(s: &str) -> Result<DurationRange, Self::Err> {
+        let sep = s.find("..").ok_or(duration::Error::InvalidRange)?;
+        let low = parse_duration(&s[..sep])?;
+        let high = parse_duration(&s[sep+2..])
+            .map_err(|e| e.offset_by(sep+2))?;
+        if low > high {
+            return Err(duration::Error::InvalidRange);
+        }
+        Ok(DurationRange { low, high })
+    }
+}
+
+/// An interval between two timestamps that has `FromStr` implementation
+///
+/// The format is ISO 8601 interval of two RFC 3339 timestamps separated
+/// by `/`, e.g. `2018-02-14T00:00:00Z/2018-02-15T00:00:00Z`. Each side
+/// uses format of `parse_rfc3339`. The start must not be later than the
+/// end, otherwise [`InvalidRange`](crate::TimestampError::InvalidRange) is
+/// returned.
+///
+/// # Example
+///
+/// ```
+/// let range = "2018-02-14T00:00:00Z/2018-02-15T00:00:00Z"
+///     .parse::<humantime::TimestampRange>().unwrap();
+/// assert_eq!(range.duration().to_string(), "1day");
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct TimestampRange {
+    start: SystemTime,
+    end: SystemTime,
+}
+
+impl TimestampRange {
+    /// Returns start of the interval
+    pub fn start(&self) -> SystemTime {
+        self.start
+    }
+    /// Returns end of the interval
+    pub fn end(&self) -> SystemTime {
+        self.end
+    }
+    /// Returns the time between start and end
+    pub fn duration(&self) -> wrapper::Duration {
+        self.end.duration_since(self.start)
+            .unwrap_or(Duration::ZERO)
+            .into()
+    }
+    /// Iterates over timestamps from start (inclusive) to end (exclusive)
+    ///
+    /// Each timestamp is `step` later than the previous one. Zero step
+    /// yields nothing, as does an empty interval.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let range = "2018-02-14T00:00:00Z/2018-02-14T00:01:00Z"
+    ///     .parse::<humantime::TimestampRange>().unwrap();
+    /// let buckets = range.step_by(Duration::from_secs(20))
+    ///     .map(|t| humantime::format_rfc3339(t).to_string())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(buckets, ["2018-02-14T00:00:00Z", "2018-02-14T00:00:20Z",
+    ///                      "2018-02-14T00:00:40Z"]);
+    /// ```
+    pub fn step_by(&self, step: Duration)
+        -> impl Iterator<Item = SystemTime>
+    {
+        let end = self.end;
+        iter::successors(Some(self.start), move |&t| t.checked_add(step))
+            .take_while(move |&t| step > Duration::ZERO && t < end)
+    }
+}
+
+impl FromStr for TimestampRange {
+    type Err = date::Error;
+    fn from_str(s: &str) -> Result<TimestampRange, Self::Err> {
+        let sep = s.find('/').ok_or(date::Error::InvalidFormat)?;
+        let start = parse_rfc3339(&s[..sep])?;
+        let end = parse_rfc3339(&s[sep+1..])?;
+        if start > end {
+            return Err(date::Error::InvalidRange);
+        }
+        Ok(TimestampRange { start, end })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use rand::Rng;
+
+    use super::{DurationRange, TimestampRange};
+    use crate::date::{self, parse_rfc3339};
+    use crate::duration::Error;
+
+    #[test]
+    fn valid() {
+        let r: DurationRange = "1h..2h".parse().unwrap();
+        assert_eq!(r.low(), Duration::new(3600, 0));
+        assert_eq!(r.high(), Duration::new(7200, 0));
+        let r: DurationRange = "500ms .. 1500ms".parse().unwrap();
+        assert_eq!(r.low(), Duration::new(0, 500_000_000));
+        assert_eq!(r.high(), Duration::new(1, 500_000_000));
+        let r: DurationRange = "1s..1s".parse().unwrap();
+        assert_eq!(r.low(), r.high());
+    }
+
+    #[test]
+    fn inverted() {
+        assert_eq!("2h..1h".parse::<DurationRange>(),
+                   Err(Error::InvalidRange));
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!("1h".parse::<DurationRange>(), Err(Error::InvalidRange));
+        assert_eq!("1h..".parse::<DurationRange>(), Err(Error::Empty));
+        assert_eq!("..1h".parse::<DurationRange>(), Err(Error::Empty));
+        assert_eq!("1h..2x".parse::<DurationRange>(),
+                   Err(Error::UnknownUnit {
+                       start: 5, end: 6, unit: "x".to_string(), value: 2 }));
+        assert_eq!("1h...2h".parse::<DurationRange>(),
+                   Err(Error::NumberExpected(4)));
+    }
+
+    #[test]
+    fn sample() {
+        let r: DurationRange = "1s..2s".parse().unwrap();
+        assert_eq!(r.sample(0.0), Duration::new(1, 0));
+        assert_eq!(r.sample(1.0), Duration::new(2, 0));
+        assert_eq!(r.sample(0.25), Duration::new(1, 250_000_000));
+        assert_eq!(r.sample(-1.0), Duration::new(1, 0));
+        assert_eq!(r.sample(7.0), Duration::new(2, 0));
+        for _ in 0..1000 {
+            let d = r.sample(rand::thread_rng().gen_range(0.0, 1.0));
+            assert!(d >= r.low() && d <= r.high());
+        }
+    }
+
+    #[test]
+    fn timestamp_range() {
+        let r: TimestampRange = "2018-02-14T00:00:00Z/2018-02-15T12:30:00Z"
+            .parse().unwrap();
+        assert_eq!(r.start(), parse_rfc3339("2018-02-14T00:00:00Z").unwrap());
+        assert_eq!(r.end(), parse_rfc3339("2018-02-15T12:30:00Z").unwrap());
+        assert_eq!(*r.duration(), Duration::new(131_400, 0));
+        assert_eq!(r.duration().to_string(), "1day 12h 30m");
+        let r: TimestampRange = "2018-02-14T00:00:00Z/2018-02-14T03:00:00+03:00"
+            .parse().unwrap();
+        assert_eq!(*r.duration(), Duration::ZERO);
+    }
+
+    #[test]
+    fn step_by() {
+        let r: TimestampRange = "2018-02-14T00:00:00Z/2018-02-15T00:00:00Z"
+            .parse().unwrap();
+        let hour = Duration::from_secs(3600);
+        let buckets = r.step_by(hour).collect::<Vec<_>>();
+        assert_eq!(buckets.len(), 24);
+        assert_eq!(buckets[0], r.start());
+        assert_eq!(buckets[23], r.end() - hour);
+        for pair in buckets.windows(2) {
+            assert_eq!(pair[1].duration_since(pair[0]).unwrap(), hour);
+        }
+        assert_eq!(r.step_by(Duration::from_secs(7 * 3600)).count(), 4);
+        assert_eq!(r.step_by(Duration::from_secs(86400 * 2)).count(), 1);
+        assert_eq!(r.step_by(Duration::ZERO).count(), 0);
+        let empty: TimestampRange =
+            "2018-02-14T00:00:00Z/2018-02-14T00:00:00Z".parse().unwrap();
+        assert_eq!(empty.step_by(hour).count(), 0);
+    }
+
+    #[test]
+    fn timestamp_range_invalid() {
+        assert_eq!("2018-02-15T00:00:00Z/2018-02-14T00:00:00Z"
+                   .parse::<TimestampRange>(),
+                   Err(date::Error::InvalidRange));
+        assert_eq!("2018-02-14T00:00:00Z".parse::<TimestampRange>(),
+                   Err(date::Error::InvalidFormat));
+        assert_eq!("2018-02-14T00:00:00Z/".parse::<TimestampRange>(),
+                   Err(date::Error::InvalidFormat));
+        assert_eq!("2018-02-14T00:00:00Z/2018-02-1xT00:00:00Z"
+                   .parse::<TimestampRange>(),
+                   Err(date::Error::InvalidDigit));
+    }
+}
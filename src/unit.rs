@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::duration::Error;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Time unit accepted by the duration parser
+///
+/// Units are ordered by their length, so `Unit::Hours > Unit::Minutes`.
+///
+/// `Display` writes the shortest name of the unit (`ms`, `h`, `M`), and
+/// `FromStr` accepts any name supported by the duration parser.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::Unit;
+///
+/// assert_eq!("hours".parse(), Ok(Unit::Hours));
+/// assert_eq!(Unit::Hours.to_string(), "h");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Unit {
+    /// Nanoseconds: `ns`, `nsec`, `nanos`, `nanosecond`, `nanoseconds`
+    Nanos,
+    /// Microseconds: `us`, `usec`, `microsecond`, `microseconds`
+    Micros,
+    /// Milliseconds: `ms`, `msec`, `millis`, `millisecond`, `milliseconds`
+    Millis,
+    /// Centiseconds: `cs`
+    Centis,
+    /// Deciseconds: `ds`
+    Decis,
+    /// Seconds: `s`, `sec`, `secs`, `second`, `seconds`
+    Seconds,
+    /// Minutes: `m`, `min`, `mins`, `minute`, `minutes`
+    Minutes,
+    /// Hours: `h`, `hr`, `hrs`, `hour`, `hours`
+    Hours,
+    /// Days: `d`, `day`, `days`
+    Days,
+    /// Weeks: `w`, `week`, `weeks`
+    Weeks,
+    /// Months, defined as 30.44 days: `M`, `month`, `months`
+    Months,
+    /// Years, defined as 365.25 days: `y`, `year`, `years`
+    Years,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Unit::Nanos => "ns",
+            Unit::Micros => "us",
+            Unit::Millis => "ms",
+            Unit::Centis => "cs",
+            Unit::Decis => "ds",
+            Unit::Seconds => "s",
+            Unit::Minutes => "m",
+            Unit::Hours => "h",
+            Unit::Days => "d",
+            Unit::Weeks => "w",
+            Unit::Months => "M",
+            Unit::Years => "y",
+        })
+    }
+}
+
+impl FromStr for Unit {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Unit, Error> {
+        if s.is_empty() {
+            return Err(Error::Empty);
+        }
+        unit_by_name(s).ok_or_else(|| Error::UnknownUnit {
+            start: 0,
+            end: s.len(),
+            unit: s.to_string(),
+            value: 0,
+        })
+    }
+}
+
+/// Returns the number of nanoseconds in the unit
+///
+/// These are exactly the values used by the duration parser and formatter.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{unit_nanos, Unit};
+///
+/// assert_eq!(unit_nanos(Unit::Minutes), 60_000_000_000);
+/// ```
+pub const fn unit_nanos(unit: Unit) -> u128 {
+    match unit {
+        Unit::Nanos => 1,
+        Unit::Micros => 1000,
+        Unit::Millis => 1_000_000,
+        Unit::Centis => 10_000_000,
+        Unit::Decis => 100_000_000,
+        Unit::Seconds => NANOS_PER_SEC,
+        Unit::Minutes => 60 * NANOS_PER_SEC,
+        Unit::Hours => 3600 * NANOS_PER_SEC,
+        Unit::Days => 86400 * NANOS_PER_SEC,
+        Unit::Weeks => 7 * 86400 * NANOS_PER_SEC,
+        Unit::Months => 2_630_016 * NANOS_PER_SEC,  // 30.44d
+        Unit::Years => 31_557_600 * NANOS_PER_SEC,  // 365.25d
+    }
+}
+
+/// Returns the largest unit that is not longer than the duration
+///
+/// For example, `90min` is `Hours` and `1500ms` is `Seconds`. Zero
+/// duration is `Nanos`. Centiseconds and deciseconds are never returned,
+/// so `150ms` is `Millis`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{dominant_unit, Unit};
+///
+/// assert_eq!(dominant_unit(Duration::new(5400, 0)), Unit::Hours);
+/// ```
+pub fn dominant_unit(d: Duration) -> Unit {
+    use self::Unit::*;
+
+    let nanos = d.as_nanos();
+    [Years, Months, Weeks, Days, Hours, Minutes, Seconds, Millis, Micros]
+        .iter()
+        .cloned()
+        .find(|&unit| unit_nanos(unit) <= nanos)
+        .unwrap_or(Nanos)
+}
+
+const SUPPORTED_UNITS: &[(&str, Unit)] = &[
+    ("nanoseconds", Unit::Nanos),
+    ("nanosecond", Unit::Nanos),
+    ("nanos", Unit::Nanos),
+    ("nsec", Unit::Nanos),
+    ("ns", Unit::Nanos),
+    ("microseconds", Unit::Micros),
+    ("microsecond", Unit::Micros),
+    ("usec", Unit::Micros),
+    ("us", Unit::Micros),
+    ("milliseconds", Unit::Millis),
+    ("millisecond", Unit::Millis),
+    ("millis", Unit::Millis),
+    ("msec", Unit::Millis),
+    ("ms", Unit::Millis),
+    ("cs", Unit::Centis),
+    ("ds", Unit::Decis),
+    ("seconds", Unit::Seconds),
+    ("second", Unit::Seconds),
+    ("secs", Unit::Seconds),
+    ("sec", Unit::Seconds),
+    ("s", Unit::Seconds),
+    ("minutes", Unit::Minutes),
+    ("minute", Unit::Minutes),
+    ("mins", Unit::Minutes),
+    ("min", Unit::Minutes),
+    ("m", Unit::Minutes),
+    ("hours", Unit::Hours),
+    ("hour", Unit::Hours),
+    ("hrs", Unit::Hours),
+    ("hr", Unit::Hours),
+    ("h", Unit::Hours),
+    ("days", Unit::Days),
+    ("day", Unit::Days),
+    ("d", Unit::Days),
+    ("weeks", Unit::Weeks),
+    ("week", Unit::Weeks),
+    ("w", Unit::Weeks),
+    ("months", Unit::Months),
+    ("month", Unit::Months),
+    ("M", Unit::Months),
+    ("years", Unit::Years),
+    ("year", Unit::Years),
+    ("y", Unit::Years),
+];
+
+/// Returns every unit name accepted by the duration parser
+///
+/// Names are grouped by unit, from the shortest unit to the longest one,
+/// and the longest name of each unit goes first. Useful for generating
+/// help text.
+///
+/// # Examples
+///
+/// ```
+/// use humantime::{supported_units, Unit};
+///
+/// let hours = supported_units().iter()
+///     .filter(|&&(_, unit)| unit == Unit::Hours)
+///     .map(|&(name, _)| name)
+///     .collect::<Vec<_>>();
+/// assert_eq!(hours, ["hours", "hour", "hrs", "hr", "h"]);
+/// ```
+pub fn supported_units() -> &'static [(&'static str, Unit)] {
+    SUPPORTED_UNITS
+}
+
+/// Additional unit names for the duration parser
+///
+/// This allows parsing units in other languages, see
+/// [`DurationParser::units`](crate::DurationParser::units). Names in the
+/// table take precedence over the built-in English names, which are still
+/// accepted. Names must consist of ASCII letters, as other characters
+/// don't belong to a unit in the parser.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::{DurationParser, UnitTable, Unit};
+///
+/// let mut table = UnitTable::new();
+/// table.add("Stunde", Unit::Hours).add("Stunden", Unit::Hours);
+/// let mut parser = DurationParser::new();
+/// parser.units(&table);
+/// assert_eq!(parser.parse("2 Stunden 5min"), Ok(Duration::new(7500, 0)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitTable {
+    names: HashMap<String, Unit>,
+}
+
+impl UnitTable {
+    /// Creates an empty table
+    pub fn new() -> UnitTable {
+        UnitTable::default()
+    }
+
+    /// Adds a name for the unit, replacing previous unit of the same name
+    pub fn add(&mut self, name: &str, unit: Unit) -> &mut Self {
+        self.names.insert(name.to_string(), unit);
+        self
+    }
+
+    /// Finds unit by name in this table only
+    pub fn get(&self, name: &str) -> Option<Unit> {
+        self.names.get(name).cloned()
+    }
+}
+
+/// Finds unit by its name (any of the supported variations)
+///
+/// Dispatches on the first byte and the length before comparing the whole
+/// string, as this is on the hot path of the parser. Single-letter names
+/// need no comparison at all.
+pub(crate) fn unit_by_name(name: &str) -> Option<Unit> {
+    use self::Unit::*;
+
+    let unit = match (*name.as_bytes().first()?, name.len()) {
+        (b'n', 2) if name == "ns" => Nanos,
+        (b'n', 4) if name == "nsec" => Nanos,
+        (b'n', 5) if name == "nanos" => Nanos,
+        (b'n', 10) if name == "nanosecond" => Nanos,
+        (b'n', 11) if name == "nanoseconds" => Nanos,
+        (b'u', 2) if name == "us" => Micros,
+        (b'u', 4) if name == "usec" => Micros,
+        (b'c', 2) if name == "cs" => Centis,
+        (b's', 1) => Seconds,
+        (b's', 3) if name == "sec" => Seconds,
+        (b's', 4) if name == "secs" => Seconds,
+        (b's', 6) if name == "second" => Seconds,
+        (b's', 7) if name == "seconds" => Seconds,
+        (b'm', 1) => Minutes,
+        (b'm', 2) if name == "ms" => Millis,
+        (b'm', 3) if name == "min" => Minutes,
+        (b'm', 4) => match name {
+            "mins" => Minutes,
+            "msec" => Millis,
+            _ => return None,
+        },
+        (b'm', 5) if name == "month" => Months,
+        (b'm', 6) => match name {
+            "minute" => Minutes,
+            "millis" => Millis,
+            "months" => Months,
+            _ => return None,
+        },
+        (b'm', 7) if name == "minutes" => Minutes,
+        (b'm', 11) => match name {
+            "millisecond" => Millis,
+            "microsecond" => Micros,
+            _ => return None,
+        },
+        (b'm', 12) => match name {
+            "milliseconds" => Millis,
+            "microseconds" => Micros,
+            _ => return None,
+        },
+        (b'h', 1) => Hours,
+        (b'h', 2) if name == "hr" => Hours,
+        (b'h', 3) if name == "hrs" => Hours,
+        (b'h', 4) if name == "hour" => Hours,
+        (b'h', 5) if name == "hours" => Hours,
+        (b'd', 1) => Days,
+        (b'd', 2) if name == "ds" => Decis,
+        (b'd', 3) if name == "day" => Days,
+        (b'd', 4) if name == "days" => Days,
+        (b'w', 1) => Weeks,
+        (b'w', 4) if name == "week" => Weeks,
+        (b'w', 5) if name == "weeks" => Weeks,
+        (b'M', 1) => Months,
+        (b'y', 1) => Years,
+        (b'y', 4) if name == "year" => Years,
+        (b'y', 5) if name == "years" => Years,
+        _ => return None,
+    };
+    Some(unit)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::parse_duration_with_meta;
+    use crate::duration::Error;
+    use super::{unit_nanos, unit_by_name, dominant_unit, supported_units};
+    use super::Unit;
+
+    #[test]
+    fn nanos() {
+        assert_eq!(unit_nanos(Unit::Months), 2_630_016 * 1_000_000_000);
+        assert_eq!(unit_nanos(Unit::Years), 31_557_600 * 1_000_000_000);
+        assert_eq!(unit_nanos(Unit::Weeks), 7 * unit_nanos(Unit::Days));
+        assert_eq!(unit_nanos(Unit::Decis), 10 * unit_nanos(Unit::Centis));
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(unit_by_name("M"), Some(Unit::Months));
+        assert_eq!(unit_by_name("m"), Some(Unit::Minutes));
+        assert_eq!(unit_by_name("ms"), Some(Unit::Millis));
+        assert_eq!(unit_by_name("ds"), Some(Unit::Decis));
+        assert_eq!(unit_by_name(""), None);
+        assert_eq!(unit_by_name("nights"), None);
+        assert_eq!(unit_by_name("mo"), None);
+        assert_eq!(unit_by_name("minutely"), None);
+        assert_eq!(unit_by_name("\u{b5}s"), None);
+    }
+
+    #[test]
+    fn all_supported_parse() {
+        for &(name, unit) in supported_units() {
+            assert_eq!(unit_by_name(name), Some(unit), "{}", name);
+            let (dur, max) = parse_duration_with_meta(&format!("1{}", name))
+                .unwrap();
+            assert_eq!(max, unit, "{}", name);
+            assert_eq!(dur.as_nanos(), unit_nanos(unit), "{}", name);
+        }
+    }
+
+    #[test]
+    fn display_from_str() {
+        use self::Unit::*;
+
+        for &unit in &[Nanos, Micros, Millis, Centis, Decis, Seconds, Minutes,
+                       Hours, Days, Weeks, Months, Years]
+        {
+            assert_eq!(unit.to_string().parse(), Ok(unit), "{:?}", unit);
+        }
+        for &(name, unit) in supported_units() {
+            assert_eq!(name.parse(), Ok(unit), "{}", name);
+        }
+        assert_eq!(Minutes.to_string(), "m");
+        assert_eq!(Months.to_string(), "M");
+        assert_eq!("".parse::<Unit>(), Err(Error::Empty));
+        assert_eq!("nights".parse::<Unit>(), Err(Error::UnknownUnit {
+            start: 0, end: 6, unit: "nights".to_string(), value: 0 }));
+        assert!("5min".parse::<Unit>().is_err());
+        assert!("Hours".parse::<Unit>().is_err());
+    }
+
+    #[test]
+    fn dominant() {
+        assert_eq!(dominant_unit(Duration::from_millis(1500)), Unit::Seconds);
+        assert_eq!(dominant_unit(Duration::from_secs(90*60)), Unit::Hours);
+        assert_eq!(dominant_unit(Duration::from_secs(60)), Unit::Minutes);
+        assert_eq!(dominant_unit(Duration::from_secs(59)), Unit::Seconds);
+        assert_eq!(dominant_unit(Duration::from_millis(150)), Unit::Millis);
+        assert_eq!(dominant_unit(Duration::from_secs(10*86400)), Unit::Weeks);
+        assert_eq!(dominant_unit(Duration::from_secs(400*86400)),
+                   Unit::Years);
+        assert_eq!(dominant_unit(Duration::from_nanos(999)), Unit::Nanos);
+        assert_eq!(dominant_unit(Duration::ZERO), Unit::Nanos);
+    }
+}
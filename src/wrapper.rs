@@ -1,10 +1,14 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::str::FromStr;
 use std::ops::Deref;
 use std::fmt;
-use std::time::{Duration as StdDuration, SystemTime};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration as StdDuration, Instant, SystemTime};
 
 use crate::duration::{self, parse_duration, format_duration};
 use crate::date::{self, parse_rfc3339_weak, format_rfc3339};
+use crate::iso8601::format_iso8601;
 
 /// A wrapper for duration that has `FromStr` implementation
 ///
@@ -22,7 +26,9 @@ use crate::date::{self, parse_rfc3339_weak, format_rfc3339};
 /// assert_eq!(x, Duration::new(12*3600 + 5*60, 2))
 /// ```
 ///
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/// `Debug` shows the formatted value, like `Duration("12h 5m 2ns")`.
+///
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Duration(StdDuration);
 
 /// A wrapper for SystemTime that has `FromStr` implementation
@@ -46,6 +52,178 @@ pub struct Duration(StdDuration);
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Timestamp(SystemTime);
 
+/// A duration that parses either a human-readable duration or seconds
+///
+/// This is useful for migrating configuration files where the value used
+/// to be an integer number of seconds. The string is parsed with
+/// `parse_duration` first, so `30s` and `1min` work as usual. Only if that
+/// fails because the unit is missing at the end of the input, the whole
+/// (trimmed) string is parsed as an integer number of seconds. Otherwise,
+/// or if the string isn't an integer, the original error is returned.
+///
+/// Note that `0` is accepted by `parse_duration` itself.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::FlexibleDuration;
+///
+/// let x: FlexibleDuration = "30s".parse().unwrap();
+/// assert_eq!(*x, Duration::new(30, 0));
+/// let y: FlexibleDuration = "30".parse().unwrap();
+/// assert_eq!(*y, Duration::new(30, 0));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct FlexibleDuration(StdDuration);
+
+/// A duration that remembers the text it was parsed from
+///
+/// This is useful to echo exactly what the user typed in messages. The
+/// text is borrowed when parsed with [`parse`](SourcedDuration::parse)
+/// and owned when parsed with `FromStr` (or after
+/// [`into_owned`](SourcedDuration::into_owned)). Durations converted from
+/// `std::time::Duration` have no original text.
+///
+/// `Display` writes the original text if there is one. Comparison and
+/// hashing only use the duration, so `1h` equals `60min`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use humantime::SourcedDuration;
+///
+/// let x = SourcedDuration::parse("1h  30min").unwrap();
+/// assert_eq!(*x, Duration::new(5400, 0));
+/// assert_eq!(x.original(), Some("1h  30min"));
+/// assert_eq!(x.to_string(), "1h  30min");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourcedDuration<'a> {
+    duration: StdDuration,
+    original: Option<Cow<'a, str>>,
+}
+
+impl Duration {
+    /// Parses duration from a string literal, panics if it's invalid
+    ///
+    /// This is meant for values hardcoded in the program, e.g. in static
+    /// tables, where invalid value is a bug. Panic message contains the
+    /// string, the error and its byte offset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// let x = humantime::Duration::from_str_or_panic("1h 30min");
+    /// assert_eq!(*x, Duration::new(5400, 0));
+    /// ```
+    pub fn from_str_or_panic(s: &'static str) -> Duration {
+        match parse_duration(s) {
+            Ok(dur) => Duration(dur),
+            Err(e) => match e.labels(s).first() {
+                Some((range, _)) => {
+                    panic!("invalid duration {:?} at {}: {}",
+                           s, range.start, e)
+                }
+                None => panic!("invalid duration {:?}: {}", s, e),
+            },
+        }
+    }
+
+    /// Formats duration as ISO 8601 `P1DT2H30M`
+    ///
+    /// Only days, hours, minutes and seconds (with fractional part if
+    /// needed) are emitted, as years and months have no fixed length.
+    /// The result can be parsed back with `parse_iso8601_duration`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let d: humantime::Duration = "1day 2h 30min".parse().unwrap();
+    /// assert_eq!(d.to_iso8601(), "P1DT2H30M");
+    /// ```
+    pub fn to_iso8601(&self) -> String {
+        format_iso8601(self.0)
+    }
+
+    /// Formats duration with spans joined by the separator `2h-37m`
+    ///
+    /// Spans are the same as in `Display` output, which uses a single
+    /// space as the separator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let d: humantime::Duration = "2h 37min".parse().unwrap();
+    /// assert_eq!(d.display_with_separator("-"), "2h-37m");
+    /// ```
+    pub fn display_with_separator(&self, sep: &str) -> String {
+        // spans never contain spaces themselves
+        format_duration(self.0).to_string().replace(' ', sep)
+    }
+
+    /// Returns the instant this duration from now
+    ///
+    /// This is `Instant::now() + duration`, useful for timeouts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting instant can't be represented on the
+    /// platform, like `Instant + Duration` does. Validate huge values from
+    /// configuration beforehand.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Instant;
+    ///
+    /// let timeout: humantime::Duration = "30s".parse().unwrap();
+    /// assert!(timeout.deadline() > Instant::now());
+    /// ```
+    pub fn deadline(&self) -> Instant {
+        Instant::now() + self.0
+    }
+
+    /// Splits the duration into parts before and after the boundary
+    ///
+    /// The second part is zero if `at` is not less than the duration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let d: humantime::Duration = "90s".parse().unwrap();
+    /// let (before, after) = d.split_at(Duration::from_secs(60));
+    /// assert_eq!(before.to_string(), "1m");
+    /// assert_eq!(after.to_string(), "30s");
+    /// ```
+    pub fn split_at(&self, at: StdDuration) -> (Duration, Duration) {
+        let before = self.0.min(at);
+        (Duration(before), Duration(self.0 - before))
+    }
+
+    /// Returns seconds and nanoseconds as in protobuf `Duration` message
+    ///
+    /// Durations longer than `i64::MAX` seconds are saturated to
+    /// `(i64::MAX, 999_999_999)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let d: humantime::Duration = "1min 1.5s".parse().unwrap();
+    /// assert_eq!(d.as_secs_nanos(), (61, 500_000_000));
+    /// ```
+    pub fn as_secs_nanos(&self) -> (i64, i32) {
+        match i64::try_from(self.0.as_secs()) {
+            Ok(secs) => (secs, self.0.subsec_nanos() as i32),
+            Err(_) => (i64::MAX, 999_999_999),
+        }
+    }
+}
+
 impl AsRef<StdDuration> for Duration {
     fn as_ref(&self) -> &StdDuration { &self.0 }
 }
@@ -55,14 +233,22 @@ impl Deref for Duration {
     fn deref(&self) -> &StdDuration { &self.0 }
 }
 
-impl Into<StdDuration> for Duration {
-    fn into(self) -> StdDuration { self.0 }
+impl From<Duration> for StdDuration {
+    fn from(dur: Duration) -> StdDuration { dur.0 }
 }
 
 impl From<StdDuration> for Duration {
     fn from(dur: StdDuration) -> Duration { Duration(dur) }
 }
 
+impl fmt::Debug for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Duration")
+            .field(&format_duration(self.0).to_string())
+            .finish()
+    }
+}
+
 impl FromStr for Duration {
     type Err = duration::Error;
     fn from_str(s: &str) -> Result<Duration, Self::Err> {
@@ -76,6 +262,119 @@ impl fmt::Display for Duration {
     }
 }
 
+impl AsRef<StdDuration> for FlexibleDuration {
+    fn as_ref(&self) -> &StdDuration { &self.0 }
+}
+
+impl Deref for FlexibleDuration {
+    type Target = StdDuration;
+    fn deref(&self) -> &StdDuration { &self.0 }
+}
+
+impl From<FlexibleDuration> for StdDuration {
+    fn from(dur: FlexibleDuration) -> StdDuration { dur.0 }
+}
+
+impl From<StdDuration> for FlexibleDuration {
+    fn from(dur: StdDuration) -> FlexibleDuration { FlexibleDuration(dur) }
+}
+
+impl FromStr for FlexibleDuration {
+    type Err = duration::Error;
+    fn from_str(s: &str) -> Result<FlexibleDuration, Self::Err> {
+        let err = match parse_duration(s) {
+            Ok(dur) => return Ok(FlexibleDuration(dur)),
+            Err(e) => e,
+        };
+        match err {
+            duration::Error::UnknownUnit { ref unit, end, .. }
+                if unit.is_empty() && end == s.len()
+            => s.trim().parse::<u64>()
+                .map(|secs| FlexibleDuration(StdDuration::from_secs(secs)))
+                .map_err(|_| err),
+            _ => Err(err),
+        }
+    }
+}
+
+impl fmt::Display for FlexibleDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_duration(self.0).fmt(f)
+    }
+}
+
+impl<'a> SourcedDuration<'a> {
+    /// Parses duration borrowing the original text
+    pub fn parse(s: &'a str) -> Result<SourcedDuration<'a>, duration::Error> {
+        Ok(SourcedDuration {
+            duration: parse_duration(s)?,
+            original: Some(Cow::Borrowed(s)),
+        })
+    }
+
+    /// Returns the text this duration was parsed from
+    pub fn original(&self) -> Option<&str> {
+        self.original.as_ref().map(|s| &s[..])
+    }
+
+    /// Copies the original text so the value doesn't borrow the input
+    pub fn into_owned(self) -> SourcedDuration<'static> {
+        SourcedDuration {
+            duration: self.duration,
+            original: self.original.map(|s| Cow::Owned(s.into_owned())),
+        }
+    }
+}
+
+impl<'a> PartialEq for SourcedDuration<'a> {
+    fn eq(&self, other: &SourcedDuration<'a>) -> bool {
+        self.duration == other.duration
+    }
+}
+
+impl<'a> Eq for SourcedDuration<'a> {}
+
+impl<'a> Hash for SourcedDuration<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.duration.hash(state)
+    }
+}
+
+impl<'a> AsRef<StdDuration> for SourcedDuration<'a> {
+    fn as_ref(&self) -> &StdDuration { &self.duration }
+}
+
+impl<'a> Deref for SourcedDuration<'a> {
+    type Target = StdDuration;
+    fn deref(&self) -> &StdDuration { &self.duration }
+}
+
+impl<'a> From<SourcedDuration<'a>> for StdDuration {
+    fn from(dur: SourcedDuration<'a>) -> StdDuration { dur.duration }
+}
+
+impl From<StdDuration> for SourcedDuration<'static> {
+    fn from(dur: StdDuration) -> SourcedDuration<'static> {
+        SourcedDuration { duration: dur, original: None }
+    }
+}
+
+impl FromStr for SourcedDuration<'static> {
+    type Err = duration::Error;
+    fn from_str(s: &str) -> Result<SourcedDuration<'static>, Self::Err> {
+        SourcedDuration::parse(s).map(SourcedDuration::into_owned)
+    }
+}
+
+impl<'a> fmt::Display for SourcedDuration<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.original {
+            Some(ref s) => f.write_str(s),
+            None => format_duration(self.duration).fmt(f),
+        }
+    }
+}
+
 impl AsRef<SystemTime> for Timestamp {
     fn as_ref(&self) -> &SystemTime { &self.0 }
 }
@@ -85,8 +384,8 @@ impl Deref for Timestamp {
     fn deref(&self) -> &SystemTime { &self.0 }
 }
 
-impl Into<SystemTime> for Timestamp {
-    fn into(self) -> SystemTime { self.0 }
+impl From<Timestamp> for SystemTime {
+    fn from(ts: Timestamp) -> SystemTime { ts.0 }
 }
 
 impl From<SystemTime> for Timestamp {
@@ -105,3 +404,142 @@ impl fmt::Display for Timestamp {
         format_rfc3339(self.0).fmt(f)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration as StdDuration, Instant};
+
+    use crate::duration::Error;
+    use super::{Duration, FlexibleDuration, SourcedDuration};
+
+    #[test]
+    fn from_str_or_panic() {
+        assert_eq!(*Duration::from_str_or_panic("2h 37min"),
+                   StdDuration::new(9420, 0));
+    }
+
+    #[test]
+    fn split_at() {
+        let d = Duration::from_str_or_panic("1h 30min");
+        let hour = StdDuration::from_secs(3600);
+        assert_eq!(d.split_at(hour),
+                   (Duration::from(hour), Duration::from_str_or_panic("30m")));
+        assert_eq!(d.split_at(StdDuration::ZERO),
+                   (Duration::from(StdDuration::ZERO), d));
+        assert_eq!(d.split_at(*d), (d, Duration::from(StdDuration::ZERO)));
+        assert_eq!(d.split_at(hour * 2),
+                   (d, Duration::from(StdDuration::ZERO)));
+    }
+
+    #[test]
+    fn deadline() {
+        let d = Duration::from_str_or_panic("1h");
+        let before = Instant::now();
+        let deadline = d.deadline();
+        let after = Instant::now();
+        assert!(deadline >= before + *d);
+        assert!(deadline <= after + *d);
+        assert!(deadline - before - *d < StdDuration::from_secs(1));
+    }
+
+    #[test]
+    fn display_with_separator() {
+        let d = Duration::from_str_or_panic("1year 2h 37min 1ms");
+        assert_eq!(d.display_with_separator(" "), "1year 2h 37m 1ms");
+        assert_eq!(d.display_with_separator(""), "1year2h37m1ms");
+        assert_eq!(d.display_with_separator("-"), "1year-2h-37m-1ms");
+        assert_eq!(d.display_with_separator(", "), "1year, 2h, 37m, 1ms");
+        let zero = Duration::from(StdDuration::ZERO);
+        assert_eq!(zero.display_with_separator("-"), "0s");
+    }
+
+    #[test]
+    #[should_panic(expected="invalid duration \"2h 37nights\" at 5")]
+    fn from_str_or_panic_invalid() {
+        Duration::from_str_or_panic("2h 37nights");
+    }
+
+    #[test]
+    #[should_panic(expected="invalid duration \"2h 3$\" at 4")]
+    fn from_str_or_panic_invalid_char() {
+        Duration::from_str_or_panic("2h 3$");
+    }
+
+    #[test]
+    fn flexible() {
+        let parse = |s: &str| s.parse::<FlexibleDuration>().map(|d| *d);
+        assert_eq!(parse("30s"), Ok(StdDuration::new(30, 0)));
+        assert_eq!(parse("30"), Ok(StdDuration::new(30, 0)));
+        assert_eq!(parse(" 30 "), Ok(StdDuration::new(30, 0)));
+        assert_eq!(parse("0"), Ok(StdDuration::new(0, 0)));
+        assert_eq!(parse("1min 30s"), Ok(StdDuration::new(90, 0)));
+        assert_eq!(parse("30x"), Err(Error::UnknownUnit {
+            start: 2, end: 3, unit: "x".to_string(), value: 30 }));
+        assert_eq!(parse("1min 30"), Err(Error::UnknownUnit {
+            start: 7, end: 7, unit: "".to_string(), value: 30 }));
+        assert_eq!(parse("1.5"), Err(Error::UnknownUnit {
+            start: 3, end: 3, unit: "".to_string(), value: 1 }));
+    }
+
+    #[test]
+    fn sourced() {
+        let input = String::from("1h  30min");
+        let dur = SourcedDuration::parse(&input).unwrap();
+        assert_eq!(*dur, StdDuration::new(5400, 0));
+        assert_eq!(dur.original(), Some("1h  30min"));
+        assert_eq!(dur.to_string(), "1h  30min");
+        let owned = dur.into_owned();
+        drop(input);
+        assert_eq!(owned.original(), Some("1h  30min"));
+
+        let parsed: SourcedDuration = "2min".parse().unwrap();
+        assert_eq!(parsed.original(), Some("2min"));
+        assert_eq!(StdDuration::from(parsed), StdDuration::new(120, 0));
+
+        let plain = SourcedDuration::from(StdDuration::new(90, 0));
+        assert_eq!(plain.original(), None);
+        assert_eq!(plain.to_string(), "1m 30s");
+
+        assert_eq!(SourcedDuration::parse("2nights"), Err(Error::UnknownUnit {
+            start: 1, end: 7, unit: "nights".to_string(), value: 2 }));
+    }
+
+    #[test]
+    fn sourced_eq() {
+        use std::collections::HashSet;
+
+        let hour = SourcedDuration::parse("1h").unwrap();
+        assert_eq!(hour, SourcedDuration::parse("60min").unwrap());
+        assert_eq!(hour, SourcedDuration::from(StdDuration::new(3600, 0)));
+        assert_ne!(hour, SourcedDuration::parse("61min").unwrap());
+        let set: HashSet<_> = vec![
+            hour,
+            SourcedDuration::parse("3600s").unwrap(),
+            "60m".parse().unwrap(),
+        ].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn secs_nanos() {
+        for &(secs, nanos) in &[(0, 0), (61, 500_000_000), (1, 999_999_999),
+                                (i64::MAX as u64, 1)]
+        {
+            let inner = StdDuration::new(secs, nanos);
+            let (s, n) = Duration::from(inner).as_secs_nanos();
+            assert_eq!(s as u64, inner.as_secs());
+            assert_eq!(n as u32, inner.subsec_nanos());
+        }
+        let max = Duration::from(StdDuration::new(u64::MAX, 5));
+        assert_eq!(max.as_secs_nanos(), (i64::MAX, 999_999_999));
+    }
+
+    #[test]
+    fn debug() {
+        let d: Duration = "2h 37min".parse().unwrap();
+        assert_eq!(format!("{:?}", d), r#"Duration("2h 37m")"#);
+        let d = Duration::from(StdDuration::new(0, 0));
+        assert_eq!(format!("{:?}", d), r#"Duration("0s")"#);
+        assert_eq!(format!("{:?}", Some(d)), r#"Some(Duration("0s"))"#);
+    }
+}